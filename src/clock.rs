@@ -0,0 +1,75 @@
+// Shows the time of day instead of a countdown, for when afk is left running between streams.
+use std::time::Duration;
+
+use chrono::{DateTime, Local, Timelike};
+
+#[derive(Default)]
+pub struct Clock {
+    pub twelve_hour: bool,
+    pub seconds: bool,
+    pub date: bool,
+}
+
+impl Clock {
+    pub fn time(&self, now: DateTime<Local>) -> String {
+        let format = match (self.twelve_hour, self.seconds) {
+            (false, false) => "%H:%M",
+            (false, true) => "%H:%M:%S",
+            (true, false) => "%-I:%M %p",
+            (true, true) => "%-I:%M:%S %p",
+        };
+        now.format(format).to_string()
+    }
+
+    // e.g. Sunday 18 October 2026
+    pub fn date(&self, now: DateTime<Local>) -> Option<String> {
+        self.date.then(|| now.format("%A %-d %B %Y").to_string())
+    }
+
+    // how long until the time shown changes
+    pub fn until_next(&self, now: DateTime<Local>) -> Duration {
+        let to_next_second = Duration::from_millis(1000 - (now.timestamp_subsec_millis() % 1000) as u64);
+        if self.seconds {
+            to_next_second
+        } else {
+            to_next_second + Duration::from_secs(59 - now.second().min(59) as u64)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: u32, min: u32, sec: u32, ms: i64) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 10, 18, hour, min, sec).unwrap() + chrono::Duration::milliseconds(ms)
+    }
+
+    #[test]
+    fn formats_the_time() {
+        let clock = |twelve_hour, seconds| Clock { twelve_hour, seconds, date: false };
+        assert_eq!(clock(false, false).time(at(14, 5, 9, 0)), "14:05");
+        assert_eq!(clock(false, true).time(at(14, 5, 9, 0)), "14:05:09");
+        assert_eq!(clock(true, false).time(at(14, 5, 9, 0)), "2:05 PM");
+        assert_eq!(clock(true, true).time(at(0, 5, 9, 0)), "12:05:09 AM");
+    }
+
+    #[test]
+    fn formats_the_date() {
+        assert_eq!(Clock::default().date(at(14, 5, 9, 0)), None);
+        let clock = Clock { date: true, ..Clock::default() };
+        assert_eq!(clock.date(at(14, 5, 9, 0)).as_deref(), Some("Sunday 18 October 2026"));
+    }
+
+    #[test]
+    fn waits_until_the_time_changes() {
+        let clock = Clock { seconds: true, ..Clock::default() };
+        assert_eq!(clock.until_next(at(14, 5, 9, 250)), Duration::from_millis(750));
+        assert_eq!(clock.until_next(at(14, 5, 9, 0)), Duration::from_secs(1));
+
+        let clock = Clock::default();
+        assert_eq!(clock.until_next(at(14, 5, 9, 250)), Duration::from_millis(50_750));
+        assert_eq!(clock.until_next(at(14, 5, 59, 0)), Duration::from_secs(1));
+    }
+}
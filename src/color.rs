@@ -0,0 +1,436 @@
+// Parses the colors given on the command line:
+//
+//     red, bright-red, orange, rebeccapurple    the eight terminal colors, their bright variants and CSS names
+//     42,42,42 or "42 42 42"                    RGB
+//     #ff8800 or #f80                           hex
+//     ansi:208                                  an index into the 256 color palette
+//     hsl(30, 100%, 50%)                        hue, saturation and lightness
+use std::env;
+
+use ansi_term::{Colour, Style};
+
+// the colors every terminal has, so they follow the terminal theme
+const BASIC: [(&str, Colour); 8] = [
+    ("black", Colour::Black),
+    ("red", Colour::Red),
+    ("green", Colour::Green),
+    ("yellow", Colour::Yellow),
+    ("blue", Colour::Blue),
+    ("purple", Colour::Purple),
+    ("cyan", Colour::Cyan),
+    ("white", Colour::White),
+];
+
+// the CSS named colors, minus the ones above
+const NAMED: [(&str, (u8, u8, u8)); 140] = [
+    ("aliceblue", (240, 248, 255)),
+    ("antiquewhite", (250, 235, 215)),
+    ("aqua", (0, 255, 255)),
+    ("aquamarine", (127, 255, 212)),
+    ("azure", (240, 255, 255)),
+    ("beige", (245, 245, 220)),
+    ("bisque", (255, 228, 196)),
+    ("blanchedalmond", (255, 235, 205)),
+    ("blueviolet", (138, 43, 226)),
+    ("brown", (165, 42, 42)),
+    ("burlywood", (222, 184, 135)),
+    ("cadetblue", (95, 158, 160)),
+    ("chartreuse", (127, 255, 0)),
+    ("chocolate", (210, 105, 30)),
+    ("coral", (255, 127, 80)),
+    ("cornflowerblue", (100, 149, 237)),
+    ("cornsilk", (255, 248, 220)),
+    ("crimson", (220, 20, 60)),
+    ("darkblue", (0, 0, 139)),
+    ("darkcyan", (0, 139, 139)),
+    ("darkgoldenrod", (184, 134, 11)),
+    ("darkgray", (169, 169, 169)),
+    ("darkgreen", (0, 100, 0)),
+    ("darkgrey", (169, 169, 169)),
+    ("darkkhaki", (189, 183, 107)),
+    ("darkmagenta", (139, 0, 139)),
+    ("darkolivegreen", (85, 107, 47)),
+    ("darkorange", (255, 140, 0)),
+    ("darkorchid", (153, 50, 204)),
+    ("darkred", (139, 0, 0)),
+    ("darksalmon", (233, 150, 122)),
+    ("darkseagreen", (143, 188, 143)),
+    ("darkslateblue", (72, 61, 139)),
+    ("darkslategray", (47, 79, 79)),
+    ("darkslategrey", (47, 79, 79)),
+    ("darkturquoise", (0, 206, 209)),
+    ("darkviolet", (148, 0, 211)),
+    ("deeppink", (255, 20, 147)),
+    ("deepskyblue", (0, 191, 255)),
+    ("dimgray", (105, 105, 105)),
+    ("dimgrey", (105, 105, 105)),
+    ("dodgerblue", (30, 144, 255)),
+    ("firebrick", (178, 34, 34)),
+    ("floralwhite", (255, 250, 240)),
+    ("forestgreen", (34, 139, 34)),
+    ("fuchsia", (255, 0, 255)),
+    ("gainsboro", (220, 220, 220)),
+    ("ghostwhite", (248, 248, 255)),
+    ("gold", (255, 215, 0)),
+    ("goldenrod", (218, 165, 32)),
+    ("gray", (128, 128, 128)),
+    ("greenyellow", (173, 255, 47)),
+    ("grey", (128, 128, 128)),
+    ("honeydew", (240, 255, 240)),
+    ("hotpink", (255, 105, 180)),
+    ("indianred", (205, 92, 92)),
+    ("indigo", (75, 0, 130)),
+    ("ivory", (255, 255, 240)),
+    ("khaki", (240, 230, 140)),
+    ("lavender", (230, 230, 250)),
+    ("lavenderblush", (255, 240, 245)),
+    ("lawngreen", (124, 252, 0)),
+    ("lemonchiffon", (255, 250, 205)),
+    ("lightblue", (173, 216, 230)),
+    ("lightcoral", (240, 128, 128)),
+    ("lightcyan", (224, 255, 255)),
+    ("lightgoldenrodyellow", (250, 250, 210)),
+    ("lightgray", (211, 211, 211)),
+    ("lightgreen", (144, 238, 144)),
+    ("lightgrey", (211, 211, 211)),
+    ("lightpink", (255, 182, 193)),
+    ("lightsalmon", (255, 160, 122)),
+    ("lightseagreen", (32, 178, 170)),
+    ("lightskyblue", (135, 206, 250)),
+    ("lightslategray", (119, 136, 153)),
+    ("lightslategrey", (119, 136, 153)),
+    ("lightsteelblue", (176, 196, 222)),
+    ("lightyellow", (255, 255, 224)),
+    ("lime", (0, 255, 0)),
+    ("limegreen", (50, 205, 50)),
+    ("linen", (250, 240, 230)),
+    ("magenta", (255, 0, 255)),
+    ("maroon", (128, 0, 0)),
+    ("mediumaquamarine", (102, 205, 170)),
+    ("mediumblue", (0, 0, 205)),
+    ("mediumorchid", (186, 85, 211)),
+    ("mediumpurple", (147, 112, 219)),
+    ("mediumseagreen", (60, 179, 113)),
+    ("mediumslateblue", (123, 104, 238)),
+    ("mediumspringgreen", (0, 250, 154)),
+    ("mediumturquoise", (72, 209, 204)),
+    ("mediumvioletred", (199, 21, 133)),
+    ("midnightblue", (25, 25, 112)),
+    ("mintcream", (245, 255, 250)),
+    ("mistyrose", (255, 228, 225)),
+    ("moccasin", (255, 228, 181)),
+    ("navajowhite", (255, 222, 173)),
+    ("navy", (0, 0, 128)),
+    ("oldlace", (253, 245, 230)),
+    ("olive", (128, 128, 0)),
+    ("olivedrab", (107, 142, 35)),
+    ("orange", (255, 165, 0)),
+    ("orangered", (255, 69, 0)),
+    ("orchid", (218, 112, 214)),
+    ("palegoldenrod", (238, 232, 170)),
+    ("palegreen", (152, 251, 152)),
+    ("paleturquoise", (175, 238, 238)),
+    ("palevioletred", (219, 112, 147)),
+    ("papayawhip", (255, 239, 213)),
+    ("peachpuff", (255, 218, 185)),
+    ("peru", (205, 133, 63)),
+    ("pink", (255, 192, 203)),
+    ("plum", (221, 160, 221)),
+    ("powderblue", (176, 224, 230)),
+    ("rebeccapurple", (102, 51, 153)),
+    ("rosybrown", (188, 143, 143)),
+    ("royalblue", (65, 105, 225)),
+    ("saddlebrown", (139, 69, 19)),
+    ("salmon", (250, 128, 114)),
+    ("sandybrown", (244, 164, 96)),
+    ("seagreen", (46, 139, 87)),
+    ("seashell", (255, 245, 238)),
+    ("sienna", (160, 82, 45)),
+    ("silver", (192, 192, 192)),
+    ("skyblue", (135, 206, 235)),
+    ("slateblue", (106, 90, 205)),
+    ("slategray", (112, 128, 144)),
+    ("slategrey", (112, 128, 144)),
+    ("snow", (255, 250, 250)),
+    ("springgreen", (0, 255, 127)),
+    ("steelblue", (70, 130, 180)),
+    ("tan", (210, 180, 140)),
+    ("teal", (0, 128, 128)),
+    ("thistle", (216, 191, 216)),
+    ("tomato", (255, 99, 71)),
+    ("turquoise", (64, 224, 208)),
+    ("violet", (238, 130, 238)),
+    ("wheat", (245, 222, 179)),
+    ("whitesmoke", (245, 245, 245)),
+    ("yellowgreen", (154, 205, 50)),
+];
+
+// the usual xterm values for the first 16 colors of the palette
+const XTERM: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+pub fn parse_color(color: &str) -> Result<Colour, String> {
+    let color = color.trim().to_lowercase();
+
+    if let Some(hex) = color.strip_prefix('#') {
+        return parse_hex(hex).ok_or_else(|| format!("Cannot parse hex color #{hex}, expected #rrggbb or #rgb"));
+    }
+
+    if let Some(index) = color.strip_prefix("ansi:") {
+        return match index.trim().parse() {
+            Ok(index) => Ok(Colour::Fixed(index)),
+            Err(_) => Err(format!("Cannot parse {color}, expected a palette index from ansi:0 to ansi:255")),
+        };
+    }
+
+    if let Some(hsl) = color.strip_prefix("hsl(") {
+        return parse_hsl(hsl.strip_suffix(')').unwrap_or(hsl))
+            .map_err(|e| format!("{e} in {color}, expected hsl(hue, saturation%, lightness%)"));
+    }
+
+    // RGB color value formatted as 42,42,42 or "42 42 42"
+    if color.starts_with(|c: char| c.is_ascii_digit()) {
+        let rgb = color.split([',', ' ']).filter(|c| !c.is_empty()).collect::<Vec<_>>();
+        return match rgb[..] {
+            [r, g, b] => {
+                let channel =
+                    |c: &str| c.parse().map_err(|_| format!("RGB value {c} in {color} should be from 0 to 255"));
+                Ok(Colour::RGB(channel(r)?, channel(g)?, channel(b)?))
+            }
+            _ => Err("RGB values should have 3 numbers separated by commas".to_string()),
+        };
+    }
+
+    // bright-red, bright_red and brightred are all the same, like dark-orange and darkorange
+    let name = color.replace(['-', '_', ' '], "");
+    if let Some((_, colour)) = BASIC.iter().find(|(n, _)| *n == name) {
+        return Ok(*colour);
+    }
+    if let Some(i) = name.strip_prefix("bright").and_then(|n| BASIC.iter().position(|(b, _)| *b == n)) {
+        return Ok(Colour::Fixed(8 + i as u8));
+    }
+    match NAMED.iter().find(|(n, _)| *n == name) {
+        Some((_, (r, g, b))) => Ok(Colour::RGB(*r, *g, *b)),
+        None => Err(format!("Unknown color {color}, expected a name, r,g,b, #rrggbb, ansi:NNN or hsl(h, s%, l%)")),
+    }
+}
+
+fn parse_hex(hex: &str) -> Option<Colour> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let channel = |i: usize, len: usize| u8::from_str_radix(&hex[i * len..(i + 1) * len], 16).ok();
+    match hex.len() {
+        6 => Some(Colour::RGB(channel(0, 2)?, channel(1, 2)?, channel(2, 2)?)),
+        // #f80 is short for #ff8800
+        3 => Some(Colour::RGB(channel(0, 1)? * 17, channel(1, 1)? * 17, channel(2, 1)? * 17)),
+        _ => None,
+    }
+}
+
+fn parse_hsl(hsl: &str) -> Result<Colour, String> {
+    let parts = hsl.split([',', ' ']).filter(|p| !p.is_empty()).collect::<Vec<_>>();
+    let (h, s, l) = match parts[..] {
+        [h, s, l] => (h.trim_end_matches("deg"), s.trim_end_matches('%'), l.trim_end_matches('%')),
+        _ => return Err(format!("Found {} values instead of 3", parts.len())),
+    };
+
+    let number = |v: &str, max: f32| match v.parse::<f32>() {
+        Ok(n) if (0.0..=max).contains(&n) => Ok(n),
+        _ => Err(format!("{v} should be a number from 0 to {max}")),
+    };
+    let (h, s, l) = (number(h, 360.0)?, number(s, 100.0)? / 100.0, number(l, 100.0)? / 100.0);
+
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let x = c * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
+    let (r, g, b) = match (h / 60.0) as u32 % 6 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+
+    let channel = |v: f32| ((v + l - c / 2.0) * 255.0).round() as u8;
+    Ok(Colour::RGB(channel(r), channel(g), channel(b)))
+}
+
+// what a color looks like in most terminals, for mixing colors
+pub fn to_rgb(colour: Colour) -> (u8, u8, u8) {
+    match colour {
+        Colour::Black => XTERM[0],
+        Colour::Red => XTERM[1],
+        Colour::Green => XTERM[2],
+        Colour::Yellow => XTERM[3],
+        Colour::Blue => XTERM[4],
+        Colour::Purple => XTERM[5],
+        Colour::Cyan => XTERM[6],
+        Colour::White => XTERM[7],
+        Colour::Fixed(i @ 0..=15) => XTERM[i as usize],
+        // a 6x6x6 color cube followed by 24 shades of gray
+        Colour::Fixed(i @ 16..=231) => {
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            let i = i - 16;
+            (level(i / 36), level(i / 6 % 6), level(i % 6))
+        }
+        Colour::Fixed(i) => {
+            let gray = 8 + (i - 232) * 10;
+            (gray, gray, gray)
+        }
+        Colour::RGB(r, g, b) => (r, g, b),
+    }
+}
+
+// how many colors the terminal can show
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    TrueColor,
+    Palette,
+    Basic,
+    None,
+}
+
+impl ColorMode {
+    pub fn parse(mode: &str) -> Option<Self> {
+        match mode.to_lowercase().as_ref() {
+            "truecolor" | "24bit" => Some(Self::TrueColor),
+            "256" => Some(Self::Palette),
+            "16" | "8" => Some(Self::Basic),
+            "none" => Some(Self::None),
+            _ => None,
+        }
+    }
+
+    // the same guesses most terminal programs make, see https://no-color.org for NO_COLOR
+    pub fn detect() -> Self {
+        let var = |name: &str| env::var(name).unwrap_or_default().to_lowercase();
+
+        if !var("NO_COLOR").is_empty() {
+            return Self::None;
+        }
+
+        let (colorterm, term) = (var("COLORTERM"), var("TERM"));
+        if colorterm == "truecolor" || colorterm == "24bit" || term.contains("direct") {
+            Self::TrueColor
+        } else if term.contains("256") {
+            Self::Palette
+        } else {
+            Self::Basic
+        }
+    }
+
+    // styles without colors the terminal cannot show. without colors there is no styling at all
+    pub fn style(self, style: Style) -> Style {
+        if self == Self::None {
+            return Style::new();
+        }
+
+        Style {
+            foreground: style.foreground.map(|c| self.colour(c)),
+            background: style.background.map(|c| self.colour(c)),
+            ..style
+        }
+    }
+
+    pub fn colour(self, colour: Colour) -> Colour {
+        match (self, colour) {
+            (Self::TrueColor | Self::None, colour) => colour,
+            (Self::Palette, Colour::RGB(r, g, b)) => Colour::Fixed(nearest_fixed((r, g, b))),
+            (Self::Palette, colour) => colour,
+            // the bright colors only have 256 color escapes in ansi_term, so they become the plain ones
+            (Self::Basic, Colour::Fixed(i @ 8..=15)) => BASIC[i as usize - 8].1,
+            (Self::Basic, Colour::Fixed(_) | Colour::RGB(..)) => {
+                let rgb = to_rgb(colour);
+                let nearest = (0..BASIC.len()).min_by_key(|i| distance(rgb, XTERM[*i])).unwrap_or(7);
+                BASIC[nearest].1
+            }
+            (Self::Basic, colour) => colour,
+        }
+    }
+}
+
+// the closest match in the color cube or the grays of the 256 color palette
+fn nearest_fixed(rgb: (u8, u8, u8)) -> u8 {
+    let level = |v: u8| match v {
+        0..=47 => 0,
+        48..=114 => 1,
+        v => (v - 35) / 40,
+    };
+    let cube = 16 + 36 * level(rgb.0) + 6 * level(rgb.1) + level(rgb.2);
+
+    let average = (rgb.0 as u16 + rgb.1 as u16 + rgb.2 as u16) / 3;
+    let gray = 232 + (average.saturating_sub(3) / 10).min(23) as u8;
+
+    [cube, gray].into_iter().min_by_key(|i| distance(rgb, to_rgb(Colour::Fixed(*i)))).unwrap_or(cube)
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex() {
+        assert_eq!(parse_color("#ff8800"), Ok(Colour::RGB(255, 136, 0)));
+        assert_eq!(parse_color("#f80"), Ok(Colour::RGB(255, 136, 0)));
+        assert!(parse_color("#ff88").is_err());
+        assert!(parse_color("#ggg").is_err());
+    }
+
+    #[test]
+    fn rgb() {
+        assert_eq!(parse_color("42,42,42"), Ok(Colour::RGB(42, 42, 42)));
+        assert_eq!(parse_color("42 43 44"), Ok(Colour::RGB(42, 43, 44)));
+        assert!(parse_color("42,42").is_err());
+        assert!(parse_color("256,0,0").is_err());
+    }
+
+    #[test]
+    fn ansi() {
+        assert_eq!(parse_color("ansi:208"), Ok(Colour::Fixed(208)));
+        assert!(parse_color("ansi:300").is_err());
+    }
+
+    #[test]
+    fn hsl() {
+        assert_eq!(parse_color("hsl(0, 100%, 50%)"), Ok(Colour::RGB(255, 0, 0)));
+        assert!(parse_color("hsl(400, 100%, 50%)").is_err());
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(parse_color("Red"), Ok(Colour::Red));
+        assert_eq!(parse_color("bright-red"), Ok(Colour::Fixed(9)));
+        assert_eq!(parse_color("dark_orange"), Ok(Colour::RGB(255, 140, 0)));
+        assert!(parse_color("reddish").is_err());
+    }
+
+    #[test]
+    fn palette() {
+        assert_eq!(to_rgb(Colour::Fixed(196)), (255, 0, 0));
+        assert_eq!(ColorMode::Palette.colour(Colour::RGB(255, 0, 0)), Colour::Fixed(196));
+        assert_eq!(ColorMode::Basic.colour(Colour::RGB(200, 0, 0)), Colour::Red);
+    }
+}
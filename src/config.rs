@@ -0,0 +1,367 @@
+// Loads defaults and named profiles from a small TOML config file:
+//
+//     minutes = 5
+//     color = "42,42,42"
+//     center_timer = true
+//     timer_padding = [1, 2]
+//
+//     [profile.lunch]
+//     messages = ["Lunch", "Back soon"]
+//     hours = 1
+//
+// Every key maps onto the cli flag with the same effect, so the values are checked by `parse_flag`
+// exactly like the command line is.
+use std::{
+    env, fs,
+    io::ErrorKind,
+    iter::{self, Peekable},
+    path::PathBuf,
+    str::Chars,
+};
+
+use crate::{parse_flag, AfkConfig};
+
+enum Value {
+    Bool(bool),
+    Scalar(String),
+    List(Vec<String>),
+}
+
+struct Entry {
+    line: usize,
+    key: String,
+    value: Value,
+}
+
+#[derive(Default)]
+struct ConfigFile {
+    defaults: Vec<Entry>,
+    profiles: Vec<(String, Vec<Entry>)>,
+}
+
+fn default_path() -> Option<PathBuf> {
+    match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")),
+    }
+    .map(|dir| dir.join("afk").join("config.toml"))
+}
+
+// applies the defaults from the config file, followed by the selected profile.
+// a missing file is only an error if it was asked for explicitly
+pub fn load(config: &mut AfkConfig, path: Option<PathBuf>, profile: Option<&str>) -> Result<(), String> {
+    let explicit = path.is_some();
+    let path = match path.or_else(default_path) {
+        Some(path) => path,
+        None if profile.is_some() => return Err("Cannot find the config file to load profiles from.".to_string()),
+        None => return Ok(()),
+    };
+
+    let src = match fs::read_to_string(&path) {
+        Ok(src) => src,
+        Err(e) if e.kind() == ErrorKind::NotFound && !explicit && profile.is_none() => return Ok(()),
+        Err(e) => return Err(format!("Cannot read config file {}: {e}.", path.display())),
+    };
+
+    let file = parse(&src).map_err(|(line, e)| format!("{}:{line}: {e}", path.display()))?;
+
+    let mut entries = file.defaults;
+    if let Some(name) = profile {
+        let overrides = match file.profiles.into_iter().find(|(n, _)| n == name) {
+            Some((_, overrides)) => overrides,
+            None => return Err(format!("Unknown profile {name} in {}.", path.display())),
+        };
+        // the time is set as a whole, so a profile with an hour does not keep the default five minutes on top
+        let sets_time = overrides.iter().any(|o| is_time(&o.key));
+        entries.retain(|e| !(overrides.iter().any(|o| o.key == e.key) || sets_time && is_time(&e.key)));
+        entries.extend(overrides);
+    }
+
+    for entry in entries {
+        apply(config, &entry).map_err(|e| format!("{}:{}: {e}", path.display(), entry.line))?;
+    }
+
+    Ok(())
+}
+
+fn is_time(key: &str) -> bool {
+    matches!(key, "hours" | "minutes" | "seconds" | "duration" | "until")
+}
+
+fn flag_for(key: &str) -> String {
+    match key {
+        "hours" => "-h",
+        "minutes" => "-m",
+        "seconds" => "-s",
+        "allow_negative" | "stopwatch" => "-k",
+        "color" => "-c",
+        "use_font" => "-f",
+        "center_timer" => "-z",
+        "message_padding" => "-p",
+        "timer_padding" => "-t",
+        key => return format!("--{}", key.replace('_', "-")),
+    }
+    .to_string()
+}
+
+fn apply(config: &mut AfkConfig, entry: &Entry) -> Result<(), String> {
+    let args = match (entry.key.as_str(), &entry.value) {
+        ("message", Value::Scalar(words)) => {
+            config.words = words.clone();
+            return Ok(());
+        }
+        ("message", _) => return Err("message should be a string.".to_string()),
+        ("messages", Value::List(messages)) => {
+            config.messages.extend(messages.iter().cloned());
+            return Ok(());
+        }
+        ("messages", _) => return Err("messages should be a list of strings.".to_string()),
+        // show_zeroes is the only setting that is on by default
+        ("show_zeroes", Value::Bool(show)) => {
+            config.show_zeroes = *show;
+            return Ok(());
+        }
+        // flags that can be given more than once take a list with one value for each
+        ("warn", Value::List(values)) => {
+            return values.iter().try_for_each(|v| apply_flag(config, entry, &[flag_for(&entry.key), v.clone()]))
+        }
+        (_, Value::Bool(false)) => return Ok(()),
+        (key, Value::Bool(true)) => vec![flag_for(key)],
+        (key, Value::Scalar(v)) => vec![flag_for(key), v.clone()],
+        (key, Value::List(values)) => iter::once(flag_for(key)).chain(values.iter().cloned()).collect(),
+    };
+
+    apply_flag(config, entry, &args)
+}
+
+fn apply_flag(config: &mut AfkConfig, entry: &Entry, args: &[String]) -> Result<(), String> {
+    let mut values = args[1..].iter().peekable();
+    match parse_flag(config, &args[0], &mut values) {
+        Ok(true) if values.peek().is_none() => Ok(()),
+        Ok(true) => Err(format!("Too many values for {}.", entry.key)),
+        Ok(false) => Err(format!("Unknown setting {}.", entry.key)),
+        Err(e) => Err(e),
+    }
+}
+
+fn parse(src: &str) -> Result<ConfigFile, (usize, String)> {
+    let mut file = ConfigFile::default();
+
+    for (i, line) in src.lines().enumerate() {
+        let line_no = i + 1;
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(header) = line.strip_prefix('[') {
+            let name = parse_header(header).map_err(|e| (line_no, e))?;
+            if file.profiles.iter().any(|(n, _)| *n == name) {
+                return Err((line_no, format!("Profile {name} is defined twice.")));
+            }
+            file.profiles.push((name, Vec::new()));
+            continue;
+        }
+
+        let (key, value) = match line.split_once('=') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => return Err((line_no, "Expected key = value.".to_string())),
+        };
+
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+            return Err((line_no, format!("Invalid key {key}.")));
+        }
+
+        let value = parse_value(value).map_err(|e| (line_no, format!("{e} for {key}.")))?;
+        let entries = match file.profiles.last_mut() {
+            Some((_, entries)) => entries,
+            None => &mut file.defaults,
+        };
+
+        let key = key.replace('-', "_");
+        if entries.iter().any(|e| e.key == key) {
+            return Err((line_no, format!("Duplicate key {key}.")));
+        }
+        entries.push(Entry { line: line_no, key, value });
+    }
+
+    Ok(file)
+}
+
+// `header` is everything after the opening bracket
+fn parse_header(header: &str) -> Result<String, String> {
+    let section = match header.split_once(']') {
+        Some((section, rest)) if rest.trim().is_empty() || rest.trim().starts_with('#') => section.trim(),
+        _ => return Err("Expected a closing ] after the section name.".to_string()),
+    };
+
+    match section.split_once('.') {
+        Some(("profile" | "profiles", name)) => {
+            let name = name.trim().trim_matches('"');
+            if name.is_empty() {
+                return Err("Missing profile name.".to_string());
+            }
+            Ok(name.to_string())
+        }
+        _ => Err(format!("Unknown section [{section}], expected [profile.<name>].")),
+    }
+}
+
+fn parse_value(src: &str) -> Result<Value, String> {
+    let mut chars = src.chars().peekable();
+
+    let value = match chars.peek() {
+        Some('[') => {
+            chars.next();
+            let mut values = Vec::new();
+            loop {
+                skip_whitespace(&mut chars);
+                match chars.peek() {
+                    Some(']') => {
+                        chars.next();
+                        break;
+                    }
+                    Some(_) => values.push(parse_scalar(&mut chars)?),
+                    None => return Err("Missing ] at the end of the list".to_string()),
+                }
+                skip_whitespace(&mut chars);
+                match chars.next() {
+                    Some(',') => {}
+                    Some(']') => break,
+                    _ => return Err("Expected , or ] in the list".to_string()),
+                }
+            }
+            Value::List(values)
+        }
+        Some('"' | '\'') => Value::Scalar(parse_scalar(&mut chars)?),
+        Some(_) => match parse_scalar(&mut chars)?.as_str() {
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            number => Value::Scalar(number.to_string()),
+        },
+        None => return Err("Missing value".to_string()),
+    };
+
+    skip_whitespace(&mut chars);
+    match chars.next() {
+        None | Some('#') => Ok(value),
+        Some(c) => Err(format!("Unexpected {c} after the value")),
+    }
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars<'_>>) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+// a "basic string" with escapes, a 'literal string' or a bare number
+fn parse_scalar(chars: &mut Peekable<Chars<'_>>) -> Result<String, String> {
+    let mut value = String::new();
+
+    match chars.peek().copied() {
+        Some(quote @ ('"' | '\'')) => {
+            chars.next();
+            loop {
+                match chars.next() {
+                    Some(c) if c == quote => break,
+                    Some('\\') if quote == '"' => match chars.next() {
+                        Some('n') => value.push('\n'),
+                        Some('t') => value.push('\t'),
+                        Some(c @ ('"' | '\\')) => value.push(c),
+                        Some(c) => return Err(format!("Unknown escape \\{c}")),
+                        None => return Err("Unterminated string".to_string()),
+                    },
+                    Some(c) => value.push(c),
+                    None => return Err("Unterminated string".to_string()),
+                }
+            }
+        }
+        _ => {
+            while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '+' | '_' | '.')) {
+                value.push(c);
+            }
+
+            let is_number = value.parse::<i64>().is_ok();
+            if !is_number && value != "true" && value != "false" {
+                return Err("Expected a quoted string, number, boolean or list".to_string());
+            }
+        }
+    }
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::HAlign;
+
+    // loads `src` from a file of its own, as `load` only takes a path
+    fn load_str(name: &str, src: &str, profile: Option<&str>) -> Result<AfkConfig, String> {
+        let path = env::temp_dir().join(format!("afk-test-{}-{name}.toml", std::process::id()));
+        fs::write(&path, src).unwrap();
+        let mut config = AfkConfig::default();
+        let result = load(&mut config, Some(path.clone()), profile);
+        let _ = fs::remove_file(path);
+        result.map(|_| config)
+    }
+
+    const SRC: &str = r#"
+        # defaults
+        message = "BRB"
+        minutes = 5
+        seconds = 30
+        center_timer = true
+        timer_padding = [1, 2]
+        warn = ["2m:yellow", "30s:red"]
+
+        [profile.lunch]
+        message = 'Lunch'
+        hours = 1
+    "#;
+
+    #[test]
+    fn parses_defaults_and_profiles() {
+        let file = parse(SRC).ok().unwrap();
+        let keys = file.defaults.iter().map(|e| e.key.as_str()).collect::<Vec<_>>();
+        assert_eq!(keys, ["message", "minutes", "seconds", "center_timer", "timer_padding", "warn"]);
+        assert_eq!(file.profiles.len(), 1);
+        assert_eq!(file.profiles[0].0, "lunch");
+        assert!(matches!(&file.profiles[0].1[0].value, Value::Scalar(v) if v == "Lunch"));
+    }
+
+    #[test]
+    fn rejects_bad_syntax() {
+        let line = |src| parse(src).err().map(|(line, _)| line);
+        assert_eq!(line("minutes = 5\nminutes = 6"), Some(2));
+        assert_eq!(line("minutes"), Some(1));
+        assert_eq!(line("message = \"BRB"), Some(1));
+        assert_eq!(line("[lunch]"), Some(1));
+        assert_eq!(line("timer_padding = [1, 2"), Some(1));
+    }
+
+    #[test]
+    fn applies_defaults() {
+        let config = load_str("defaults", SRC, None).unwrap();
+        assert_eq!((config.hours, config.minutes, config.seconds), (0, 5, 30));
+        assert_eq!(config.words, "BRB");
+        assert_eq!(config.timer_padding, (1, 2));
+        assert_eq!(config.warnings.len(), 2);
+        assert!(config.timer_align == HAlign::Center);
+    }
+
+    #[test]
+    fn profile_replaces_the_time_as_a_whole() {
+        let config = load_str("profile", SRC, Some("lunch")).unwrap();
+        assert_eq!((config.hours, config.minutes, config.seconds), (1, 0, 0));
+        assert_eq!(config.words, "Lunch");
+        assert_eq!(config.warnings.len(), 2);
+    }
+
+    #[test]
+    fn reports_errors_with_the_line() {
+        assert!(load_str("unknown-profile", SRC, Some("dinner")).is_err());
+        let e = load_str("unknown-key", "minutes = 5\nsnooze = true", None).err().unwrap();
+        assert!(e.ends_with(":2: Unknown setting snooze."));
+    }
+}
@@ -0,0 +1,218 @@
+// A unix socket that takes line based commands, so a running afk can be controlled from other programs:
+//
+//     add 60 | set 5m | pause | resume | reset | restart | message "Back soon" | quit | status
+//
+// every command gets a single line reply, `ok`, `error: ...` or the status. `afk ctl` sends them.
+use std::{
+    env, fs,
+    io::{self, BufRead, BufReader, Write},
+    net::Shutdown,
+    os::unix::{
+        fs::FileTypeExt,
+        net::{UnixListener, UnixStream},
+    },
+    path::{Path, PathBuf},
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+use crate::{duration, AppEvent, Tx};
+
+pub fn default_path() -> PathBuf {
+    match env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir).join("afk.sock"),
+        _ => env::temp_dir().join(format!("afk-{}.sock", env::var("USER").unwrap_or_default())),
+    }
+}
+
+// removes the socket file again when afk exits
+pub struct Socket {
+    path: PathBuf,
+}
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+pub fn listen(path: &Path, tx: Tx) -> io::Result<Socket> {
+    // a socket file left behind by an afk that did not exit cleanly would make the bind fail.
+    // anything that is not a socket is left alone, it could be someone's file
+    if UnixStream::connect(path).is_err() {
+        match fs::symlink_metadata(path) {
+            Ok(metadata) if metadata.file_type().is_socket() => fs::remove_file(path)?,
+            Ok(_) => return Err(io::Error::new(io::ErrorKind::AlreadyExists, "the path exists and is not a socket")),
+            Err(_) => {}
+        }
+    }
+
+    let listener = UnixListener::bind(path)?;
+
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let tx = tx.clone();
+            thread::spawn(move || handle(stream, tx));
+        }
+    });
+
+    Ok(Socket { path: path.to_path_buf() })
+}
+
+fn handle(stream: UnixStream, tx: Tx) {
+    let mut writer = match stream.try_clone() {
+        Ok(writer) => writer,
+        Err(_) => return,
+    };
+
+    for line in BufReader::new(stream).lines() {
+        let line = match line {
+            Ok(line) if line.trim().is_empty() => continue,
+            Ok(line) => line,
+            Err(_) => return,
+        };
+
+        let reply = match parse_command(&line) {
+            Ok(Command::Status) => {
+                let (status_tx, status_rx) = mpsc::channel();
+                let _ = tx.send(AppEvent::Status(status_tx));
+                status_rx
+                    .recv_timeout(Duration::from_secs(1))
+                    .unwrap_or_else(|_| "error: No reply from afk.".to_string())
+            }
+            Ok(Command::Event(event)) => match tx.send(event) {
+                Ok(()) => "ok".to_string(),
+                Err(_) => "error: afk is shutting down.".to_string(),
+            },
+            Err(e) => format!("error: {e}"),
+        };
+
+        if writeln!(writer, "{reply}").is_err() {
+            return;
+        }
+    }
+}
+
+enum Command {
+    Event(AppEvent),
+    Status,
+}
+
+fn parse_command(line: &str) -> Result<Command, String> {
+    let line = line.trim();
+    let (command, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let rest = rest.trim();
+
+    let event = match command.to_lowercase().as_ref() {
+        "add" => AppEvent::ModifyTimer(seconds(command, rest)?),
+        "set" => AppEvent::SetTimer(seconds(command, rest)?),
+        "pause" => AppEvent::SetPaused(true),
+        "resume" => AppEvent::SetPaused(false),
+        "reset" => AppEvent::Reset,
+        "restart" => AppEvent::Restart,
+        "message" => {
+            let message = rest.strip_prefix('"').and_then(|m| m.strip_suffix('"')).unwrap_or(rest);
+            AppEvent::SetMessage(message.to_string())
+        }
+        "quit" => AppEvent::Quit,
+        "status" => return Ok(Command::Status),
+        _ => {
+            return Err(format!(
+                "Unknown command {command}, expected add, set, pause, resume, reset, restart, message, quit or status."
+            ))
+        }
+    };
+
+    Ok(Command::Event(event))
+}
+
+fn seconds(command: &str, value: &str) -> Result<i32, String> {
+    match value {
+        "" => Err(format!("Missing seconds after {command}.")),
+        value => duration::parse_seconds(value),
+    }
+}
+
+// `afk ctl [--socket path] command [args]`, prints the reply
+pub fn send(args: &[String]) -> Result<String, String> {
+    let (path, args) = match args {
+        [flag, path, args @ ..] if flag == "--socket" => (PathBuf::from(path), args),
+        args => (default_path(), args),
+    };
+
+    if args.is_empty() {
+        return Err("Missing command, e.g. afk ctl add 60".to_string());
+    }
+
+    // quote the arguments again, so afk ctl message "Back soon" arrives as a single message
+    let line = args
+        .iter()
+        .map(|a| if a.contains(char::is_whitespace) { format!("\"{a}\"") } else { a.to_string() })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut stream =
+        UnixStream::connect(&path).map_err(|e| format!("Cannot connect to afk at {}: {e}.", path.display()))?;
+    let mut reply = String::new();
+    writeln!(stream, "{line}")
+        .and_then(|_| stream.shutdown(Shutdown::Write))
+        .and_then(|_| BufReader::new(&stream).read_line(&mut reply))
+        .map_err(|e| format!("Cannot talk to afk at {}: {e}.", path.display()))?;
+
+    match reply.trim_end().strip_prefix("error: ") {
+        Some(error) => Err(error.to_string()),
+        None => Ok(reply.trim_end().to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(line: &str) -> AppEvent {
+        match parse_command(line) {
+            Ok(Command::Event(event)) => event,
+            Ok(Command::Status) => panic!("{line} parsed as status"),
+            Err(e) => panic!("{line}: {e}"),
+        }
+    }
+
+    #[test]
+    fn parses_commands() {
+        assert!(matches!(event("add 60"), AppEvent::ModifyTimer(60)));
+        assert!(matches!(event("add -30"), AppEvent::ModifyTimer(-30)));
+        assert!(matches!(event("SET 5m"), AppEvent::SetTimer(300)));
+        assert!(matches!(event("  pause  "), AppEvent::SetPaused(true)));
+        assert!(matches!(event("resume"), AppEvent::SetPaused(false)));
+        assert!(matches!(event("reset"), AppEvent::Reset));
+        assert!(matches!(event("restart"), AppEvent::Restart));
+        assert!(matches!(event("quit"), AppEvent::Quit));
+        assert!(matches!(parse_command("status"), Ok(Command::Status)));
+    }
+
+    #[test]
+    fn parses_messages() {
+        assert!(matches!(event("message \"Back soon\""), AppEvent::SetMessage(m) if m == "Back soon"));
+        assert!(matches!(event("message Back soon"), AppEvent::SetMessage(m) if m == "Back soon"));
+        assert!(matches!(event("message"), AppEvent::SetMessage(m) if m.is_empty()));
+    }
+
+    #[test]
+    fn rejects_bad_commands() {
+        assert!(parse_command("snooze").is_err());
+        assert!(parse_command("add").is_err());
+        assert!(parse_command("set soon").is_err());
+        assert!(parse_command("add 1000000h").is_err());
+    }
+
+    #[test]
+    fn leaves_other_files_alone() {
+        let path = env::temp_dir().join(format!("afk-test-{}-not-a-socket", std::process::id()));
+        fs::write(&path, "keep me").unwrap();
+        let (tx, _rx) = mpsc::channel();
+        assert!(listen(&path, tx).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "keep me");
+        let _ = fs::remove_file(path);
+    }
+}
@@ -0,0 +1,154 @@
+// Keeps time by the deadline the timer runs out at, rather than by counting ticks. Ticks drift a little
+// every second and stop while the computer is asleep, the wall clock does neither.
+use std::time::{Duration, SystemTime};
+
+pub struct Countdown {
+    // when the timer reaches zero while it is running
+    deadline: SystemTime,
+    // the milliseconds that were left when the timer was paused
+    paused: Option<i64>,
+    allow_negative: bool,
+}
+
+impl Countdown {
+    pub fn new(seconds: i32, allow_negative: bool) -> Self {
+        let mut countdown = Self { deadline: SystemTime::now(), paused: None, allow_negative };
+        countdown.set(seconds as i64 * 1000);
+        countdown
+    }
+
+    // in milliseconds, negative once the deadline has passed
+    pub fn remaining_ms(&self) -> i64 {
+        let remaining = self.paused.unwrap_or_else(|| match self.deadline.duration_since(SystemTime::now()) {
+            Ok(left) => left.as_millis() as i64,
+            Err(e) => -(e.duration().as_millis() as i64),
+        });
+
+        if self.allow_negative {
+            remaining
+        } else {
+            remaining.max(0)
+        }
+    }
+
+    // the whole seconds to show, rounded up so zero is shown once the time is actually up
+    pub fn remaining(&self) -> i32 {
+        -(-self.remaining_ms()).div_euclid(1000) as i32
+    }
+
+    // how long until the remaining time crosses the next multiple of `step` milliseconds,
+    // e.g. 1000 for the next second. nothing changes while paused
+    pub fn until_next(&self, step: i64) -> Option<Duration> {
+        match self.paused {
+            Some(_) => None,
+            None => Some(Duration::from_millis((self.remaining_ms() - 1).rem_euclid(step) as u64 + 1)),
+        }
+    }
+
+    pub fn set(&mut self, ms: i64) {
+        let ms = if self.allow_negative { ms } else { ms.max(0) };
+
+        match self.paused.as_mut() {
+            Some(paused) => *paused = ms,
+            None if ms >= 0 => self.deadline = SystemTime::now() + Duration::from_millis(ms as u64),
+            None => self.deadline = SystemTime::now() - Duration::from_millis(-ms as u64),
+        }
+    }
+
+    // moves the deadline, so the adjustment does not depend on when it is made
+    pub fn add(&mut self, seconds: i32) {
+        self.set(self.remaining_ms() + seconds as i64 * 1000);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.is_some()
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        if paused == self.is_paused() {
+            return;
+        }
+
+        let remaining = self.remaining_ms();
+        self.paused = paused.then_some(remaining);
+        self.set(remaining);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // paused, so nothing moves while the test runs
+    fn paused(ms: i64, allow_negative: bool) -> Countdown {
+        let mut countdown = Countdown::new(0, allow_negative);
+        countdown.set_paused(true);
+        countdown.set(ms);
+        countdown
+    }
+
+    #[test]
+    fn rounds_up_to_whole_seconds() {
+        assert_eq!(paused(1500, false).remaining(), 2);
+        assert_eq!(paused(1000, false).remaining(), 1);
+        assert_eq!(paused(1, false).remaining(), 1);
+        assert_eq!(paused(0, false).remaining(), 0);
+        assert_eq!(paused(-1, true).remaining(), 0);
+        assert_eq!(paused(-1000, true).remaining(), -1);
+        assert_eq!(paused(-1001, true).remaining(), -1);
+    }
+
+    #[test]
+    fn waits_for_the_next_step() {
+        let mut countdown = Countdown::new(0, true);
+        countdown.set(1500);
+        let wait = countdown.until_next(1000).unwrap();
+        assert!(wait <= Duration::from_millis(500) && wait > Duration::from_millis(400), "{wait:?}");
+
+        countdown.set(-200);
+        let wait = countdown.until_next(1000).unwrap();
+        assert!(wait <= Duration::from_millis(800) && wait > Duration::from_millis(700), "{wait:?}");
+
+        countdown.set_paused(true);
+        assert_eq!(countdown.until_next(1000), None);
+    }
+
+    #[test]
+    fn pausing_keeps_the_remaining_time() {
+        let mut countdown = Countdown::new(60, false);
+        countdown.set_paused(true);
+        let remaining = countdown.remaining_ms();
+        assert!(remaining > 59_900 && remaining <= 60_000);
+
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(countdown.remaining_ms(), remaining);
+
+        countdown.set_paused(false);
+        assert!(!countdown.is_paused());
+        assert!(countdown.remaining_ms() <= remaining && countdown.remaining_ms() > remaining - 100);
+    }
+
+    #[test]
+    fn stops_at_zero_without_allow_negative() {
+        let mut countdown = paused(5000, false);
+        countdown.add(-10);
+        assert_eq!(countdown.remaining_ms(), 0);
+
+        let mut countdown = paused(5000, true);
+        countdown.add(-10);
+        assert_eq!(countdown.remaining_ms(), -5000);
+
+        let countdown = Countdown::new(-5, false);
+        assert_eq!(countdown.remaining_ms(), 0);
+    }
+
+    #[test]
+    fn set_while_paused_stays_paused() {
+        let mut countdown = paused(5000, false);
+        countdown.set(90_000);
+        assert!(countdown.is_paused());
+        assert_eq!(countdown.remaining_ms(), 90_000);
+        countdown.add(30);
+        assert_eq!(countdown.remaining_ms(), 120_000);
+    }
+}
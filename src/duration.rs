@@ -0,0 +1,232 @@
+// Turns the different ways of writing a point in time into a number of seconds to count down.
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+
+// only arguments made up of digits, colons and units are treated as a duration, anything else is a message
+pub fn looks_like_duration(value: &str) -> bool {
+    value.starts_with(|c: char| c.is_ascii_digit()) && value.chars().all(|c| c.is_ascii_digit() || ":dhms".contains(c))
+}
+
+// parses `1h30m`, `90s`, `2d`, `1:30:00`, `5:30` or a number of minutes into hours, minutes and seconds.
+// the parts are not normalized, that happens when they are combined into the total number of seconds
+pub fn parse_duration(value: &str) -> Result<(i32, i32, i32), String> {
+    let value = value.trim();
+
+    if value.is_empty() {
+        return Err("The duration is empty.".to_string());
+    }
+
+    if value.starts_with('-') {
+        return Err(format!("The duration {value} cannot be negative."));
+    }
+
+    let (hours, minutes, seconds) = match value.parse() {
+        Ok(minutes) => (0, minutes, 0),
+        Err(_) if value.contains(':') => parse_clock_duration(value)?,
+        Err(_) => parse_units(value)?,
+    };
+
+    // every part fits on its own, but they still have to fit together
+    match to_seconds(hours, minutes, seconds) {
+        Some(_) => Ok((hours, minutes, seconds)),
+        None => Err(format!("{value} is too large.")),
+    }
+}
+
+pub fn to_seconds(hours: i32, minutes: i32, seconds: i32) -> Option<i32> {
+    hours.checked_mul(60 * 60)?.checked_add(minutes.checked_mul(60)?)?.checked_add(seconds)
+}
+
+// `1h30m`, `90s` or `2d`
+fn parse_units(value: &str) -> Result<(i32, i32, i32), String> {
+    let (mut hours, mut minutes, mut seconds) = (0, 0, 0);
+    let mut seen = String::new();
+    let mut number = String::new();
+
+    for c in value.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+
+        if number.is_empty() {
+            return Err(format!("Expected a number before {c} in {value}."));
+        }
+
+        if seen.contains(c) {
+            return Err(format!("The unit {c} is given more than once in {value}."));
+        }
+
+        let n = number.parse::<i32>().map_err(|_| format!("{number} is too large in {value}."))?;
+        match c {
+            'd' => hours += n.checked_mul(24).ok_or_else(|| format!("{number}d is too large in {value}."))?,
+            'h' => hours += n,
+            'm' => minutes = n,
+            's' => seconds = n,
+            _ => return Err(format!("Unknown unit {c} in {value}, expected d, h, m or s.")),
+        }
+
+        seen.push(c);
+        number.clear();
+    }
+
+    if !number.is_empty() {
+        return Err(format!("Missing unit after {number} in {value}, expected d, h, m or s."));
+    }
+
+    Ok((hours, minutes, seconds))
+}
+
+// a plain number of seconds, or any of the durations above
+pub fn parse_seconds(value: &str) -> Result<i32, String> {
+    match value.trim().parse() {
+        Ok(seconds) => Ok(seconds),
+        Err(_) => parse_duration(value)
+            .and_then(|(h, m, s)| to_seconds(h, m, s).ok_or_else(|| format!("{value} is too large."))),
+    }
+}
+
+// `M:SS`, `H:MM:SS` or `D:HH:MM:SS`, the same shape the timer is shown in
+fn parse_clock_duration(value: &str) -> Result<(i32, i32, i32), String> {
+    let mut parts = Vec::new();
+    for part in value.split(':') {
+        if part.is_empty() {
+            return Err(format!("Missing number between the colons in {value}."));
+        }
+        if !part.chars().all(|c| c.is_ascii_digit()) {
+            return Err(format!("Cannot parse {part} in {value}, expected only numbers between the colons."));
+        }
+        parts.push(part.parse::<i32>().map_err(|_| format!("{part} is too large in {value}."))?);
+    }
+
+    // everything but the leading part has to fit on the clock
+    let names = ["days", "hours", "minutes", "seconds"];
+    let limits = [i32::MAX, 24, 60, 60];
+    if parts.len() > names.len() {
+        return Err(format!("Too many parts in {value}, expected at most D:HH:MM:SS."));
+    }
+
+    let first = names.len() - parts.len();
+    for (i, part) in parts.iter().enumerate().skip(1) {
+        let unit = first + i;
+        if *part >= limits[unit] {
+            return Err(format!("The {} in {value} should be below {}.", names[unit], limits[unit]));
+        }
+    }
+
+    let mut units = [0; 4];
+    units[first..].copy_from_slice(&parts);
+    let [days, hours, minutes, seconds] = units;
+    let hours =
+        days.checked_mul(24).and_then(|d| d.checked_add(hours)).ok_or_else(|| format!("{value} is too large."))?;
+
+    Ok((hours, minutes, seconds))
+}
+
+// `HH:MM`, `HH:MM:SS`, `YYYY-MM-DD HH:MM` or `YYYY-MM-DD HH:MM:SS` in local time.
+// a time without a date that already passed today means that time tomorrow
+pub fn parse_until(value: &str, now: DateTime<Local>) -> Result<i32, String> {
+    let value = value.trim();
+
+    let target = match value.split_once([' ', 'T']) {
+        Some((date, time)) => {
+            let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .map_err(|_| format!("Cannot parse date {date}, expected YYYY-MM-DD."))?;
+            let target = local(date.and_time(parse_time(time)?))?;
+            if target <= now {
+                return Err(format!("{value} is in the past."));
+            }
+            target
+        }
+        None => {
+            let time = parse_time(value)?;
+            let today = local(now.date_naive().and_time(time))?;
+            if today > now {
+                today
+            } else {
+                local(today.naive_local() + chrono::Duration::days(1))?
+            }
+        }
+    };
+
+    // round up, so the countdown hits zero at the target rather than just before it
+    let millis = (target - now).num_milliseconds();
+    i32::try_from((millis + 999) / 1000).map_err(|_| format!("{value} is too far away."))
+}
+
+fn parse_time(time: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(time, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(time, "%H:%M"))
+        .map_err(|_| format!("Cannot parse time {time}, expected HH:MM or HH:MM:SS."))
+}
+
+fn local(datetime: NaiveDateTime) -> Result<DateTime<Local>, String> {
+    // a time skipped by daylight saving does not exist, a repeated one picks the first occurrence
+    Local.from_local_datetime(&datetime).earliest().ok_or_else(|| format!("{datetime} does not exist in local time."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn units() {
+        assert_eq!(parse_duration("1h30m"), Ok((1, 30, 0)));
+        assert_eq!(parse_duration("90s"), Ok((0, 0, 90)));
+        assert_eq!(parse_duration("2d1h"), Ok((49, 0, 0)));
+        assert_eq!(parse_duration("10"), Ok((0, 10, 0)));
+    }
+
+    #[test]
+    fn bad_units() {
+        assert!(parse_duration("1m30").is_err());
+        assert!(parse_duration("1m2m").is_err());
+        assert!(parse_duration("1x").is_err());
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("-5").is_err());
+        assert!(parse_duration("-5m").is_err());
+    }
+
+    #[test]
+    fn clock() {
+        assert_eq!(parse_duration("5:30"), Ok((0, 5, 30)));
+        assert_eq!(parse_duration("1:30:00"), Ok((1, 30, 0)));
+        assert_eq!(parse_duration("1:02:00:00"), Ok((26, 0, 0)));
+        assert_eq!(parse_duration("90:00"), Ok((0, 90, 0)));
+    }
+
+    #[test]
+    fn bad_clock() {
+        assert!(parse_duration("1:60").is_err());
+        assert!(parse_duration("1:23:59:59").is_ok());
+        assert!(parse_duration("1:24:00:00").is_err());
+        assert!(parse_duration("1::30").is_err());
+        assert!(parse_duration("1:2:3:4:5").is_err());
+    }
+
+    #[test]
+    fn too_large() {
+        assert!(parse_duration("99999999").is_err());
+        assert!(parse_duration("1000000h").is_err());
+        assert!(parse_duration("596523h").is_ok());
+        assert!(parse_duration("596523h14m8s").is_err());
+        assert!(parse_duration("99999999:00").is_err());
+        assert!(parse_seconds("1000000h").is_err());
+        assert_eq!(to_seconds(i32::MAX, 0, 0), None);
+    }
+
+    #[test]
+    fn seconds() {
+        assert_eq!(parse_seconds("45"), Ok(45));
+        assert_eq!(parse_seconds("2m"), Ok(120));
+        assert_eq!(parse_seconds("1:30"), Ok(90));
+    }
+
+    #[test]
+    fn until() {
+        let now = Local.with_ymd_and_hms(2026, 10, 18, 12, 0, 0).unwrap();
+        assert_eq!(parse_until("12:30", now), Ok(30 * 60));
+        assert_eq!(parse_until("2026-10-18 13:00:30", now), Ok(60 * 60 + 30));
+        assert!(parse_until("2026-10-18 11:00", now).is_err());
+        assert!(parse_until("25:00", now).is_err());
+    }
+}
@@ -0,0 +1,37 @@
+// FIGlet fonts are either one of the bundled fonts, picked by name, or loaded from a .flf file at runtime.
+use std::fs;
+
+use figglebit::{parse, Renderer};
+
+const BUNDLED: [(&str, &str); 3] = [
+    ("Ghost", include_str!("../resources/Ghost.flf")),
+    ("Big", include_str!("../resources/Big.flf")),
+    ("Cosmike", include_str!("../resources/Cosmike.flf")),
+];
+
+pub fn bundled(name: &str) -> Renderer {
+    let (name, src) = BUNDLED.iter().find(|(n, _)| *n == name).expect("Unknown bundled font");
+    Renderer::new(parse(src.to_string()).unwrap_or_else(|_| panic!("Failed to parse font: {name}.flf")))
+}
+
+pub fn load(name: &str) -> Result<Renderer, String> {
+    let bundled_name = name.trim_end_matches(".flf");
+    if let Some((name, _)) = BUNDLED.iter().find(|(n, _)| n.eq_ignore_ascii_case(bundled_name)) {
+        return Ok(bundled(name));
+    }
+
+    let src = match fs::read_to_string(name) {
+        Ok(src) => src,
+        Err(e) => return Err(format!("Cannot read font {name}: {e}. Bundled fonts are Ghost, Big and Cosmike.")),
+    };
+
+    // every FIGlet font starts with the signature, checking it up front gives a better error than the parser
+    if !src.starts_with("flf2a") {
+        return Err(format!("{name} is not a FIGlet font, the file should start with flf2a."));
+    }
+
+    match parse(src) {
+        Ok(font) => Ok(Renderer::new(font)),
+        Err(_) => Err(format!("Cannot parse font {name}, the file is malformed.")),
+    }
+}
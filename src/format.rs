@@ -0,0 +1,143 @@
+// Time format templates like "%M min %S" or "%Dd %H:%M".
+//
+// The largest unit in the template takes everything above it as well, so "%M:%S" shows 90:00 for an hour
+// and a half rather than 30:00. Likewise "%Dd %M" shows the hours as minutes.
+enum Part {
+    Text(String),
+    Days,
+    Hours,
+    Minutes,
+    Seconds,
+    TotalMinutes,
+    Sign,
+}
+
+pub struct Format {
+    parts: Vec<Part>,
+}
+
+impl Format {
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars();
+
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                text.push(c);
+                continue;
+            }
+
+            let part = match chars.next() {
+                Some('%') => {
+                    text.push('%');
+                    continue;
+                }
+                Some('D') => Part::Days,
+                Some('H') => Part::Hours,
+                Some('M') => Part::Minutes,
+                Some('S') => Part::Seconds,
+                Some('T') => Part::TotalMinutes,
+                Some('-') => Part::Sign,
+                Some(c) => return Err(format!("Unknown %{c} in {template}, expected %D, %H, %M, %S, %T, %- or %%")),
+                None => return Err(format!("Missing a letter after the last % in {template}")),
+            };
+
+            if !text.is_empty() {
+                parts.push(Part::Text(text.split_off(0)));
+            }
+            parts.push(part);
+        }
+
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+
+        Ok(Self { parts })
+    }
+
+    pub fn format(&self, total_sec: i32) -> String {
+        let has = |unit: fn(&Part) -> bool| self.parts.iter().any(unit);
+        let shown = [
+            has(|p| matches!(p, Part::Days)),
+            has(|p| matches!(p, Part::Hours)),
+            has(|p| matches!(p, Part::Minutes | Part::TotalMinutes)),
+            has(|p| matches!(p, Part::Seconds)),
+        ];
+
+        // each unit that is shown takes whatever the larger ones shown did not, so a unit that is left out is
+        // carried down to the next smaller one instead of being dropped
+        let total = total_sec.unsigned_abs();
+        let mut left = total;
+        let mut units = [86400, 3600, 60, 1];
+        for (unit, shown) in units.iter_mut().zip(shown) {
+            let size = *unit;
+            *unit = 0;
+            if shown {
+                *unit = left / size;
+                left %= size;
+            }
+        }
+        let [days, hours, minutes, seconds] = units;
+
+        self.parts
+            .iter()
+            .map(|part| match part {
+                Part::Text(text) => text.clone(),
+                Part::Days => days.to_string(),
+                Part::Hours => format!("{hours:0>2}"),
+                Part::Minutes => format!("{minutes:0>2}"),
+                Part::Seconds => format!("{seconds:0>2}"),
+                Part::TotalMinutes => (total / 60).to_string(),
+                Part::Sign if total_sec < 0 => "-".to_string(),
+                Part::Sign => String::new(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(template: &str, total_sec: i32) -> String {
+        Format::parse(template).unwrap().format(total_sec)
+    }
+
+    #[test]
+    fn parses_units_and_text() {
+        assert_eq!(format("%H:%M:%S", 3723), "01:02:03");
+        assert_eq!(format("%M min %S", 125), "02 min 05");
+        assert_eq!(format("100%% %S", 5), "100% 05");
+    }
+
+    #[test]
+    fn rejects_unknown_units() {
+        assert!(Format::parse("%Q").is_err());
+        assert!(Format::parse("%M:%").is_err());
+    }
+
+    #[test]
+    fn largest_unit_takes_everything_above() {
+        assert_eq!(format("%M:%S", 5400), "90:00");
+        assert_eq!(format("%S", 125), "125");
+        assert_eq!(format("%Dd %H:%M", 90000), "1d 01:00");
+    }
+
+    #[test]
+    fn total_minutes_counts_as_minutes() {
+        assert_eq!(format("%T:%S", 5405), "90:05");
+    }
+
+    #[test]
+    fn missing_units_carry_down() {
+        assert_eq!(format("%Dd %M", 86400 + 5 * 3600 + 30 * 60), "1d 330");
+        assert_eq!(format("%H %S", 3725), "01 125");
+    }
+
+    #[test]
+    fn sign() {
+        assert_eq!(format("%-%M:%S", -65), "-01:05");
+        assert_eq!(format("%-%M:%S", 65), "01:05");
+    }
+}
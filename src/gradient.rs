@@ -0,0 +1,171 @@
+// Colours text with a gradient, column by column or row by row.
+use ansi_term::{Colour, Style};
+
+use crate::color::{parse_color, to_rgb, ColorMode};
+
+pub struct Gradient {
+    stops: Vec<(u8, u8, u8)>,
+}
+
+// stops are separated by `;`, or by commas when there is none, skipping the ones inside hsl(...).
+// r,g,b stops only work with `;` as the commas in them would split them up
+fn split_stops(spec: &str) -> Vec<&str> {
+    if spec.contains(';') {
+        return spec.split(';').collect();
+    }
+
+    let mut stops = Vec::new();
+    let (mut depth, mut start) = (0, 0);
+    for (i, c) in spec.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                stops.push(&spec[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    stops.push(&spec[start..]);
+    stops
+}
+
+impl Gradient {
+    // a comma separated list of at least two colours, e.g. "#ff0080,#00c0ff" or "red,blue"
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let stops = split_stops(spec)
+            .into_iter()
+            .map(|stop| parse_color(stop).map(to_rgb).map_err(|e| format!("{e} in the gradient {spec}.")))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if stops.len() < 2 {
+            return Err(format!("A gradient needs at least two colors, got {spec}."));
+        }
+
+        Ok(Self { stops })
+    }
+
+    pub fn rainbow() -> Self {
+        let stops =
+            vec![(255, 0, 0), (255, 127, 0), (255, 255, 0), (0, 255, 0), (0, 127, 255), (75, 0, 255), (148, 0, 211)];
+        Self { stops }
+    }
+
+    // from green through yellow to red, for the timer running out
+    pub fn fade() -> Self {
+        Self { stops: vec![(0, 205, 0), (205, 205, 0), (205, 0, 0)] }
+    }
+
+    // `t` goes from 0 at the start of the text to 1 at the end. `phase` shifts the colours along, going back
+    // and forth through the gradient so there is no hard edge where it wraps around
+    pub fn colour_at(&self, t: f32, phase: f32) -> Colour {
+        let t = (t / 2.0 + phase).rem_euclid(1.0);
+        let t = if t < 0.5 { t * 2.0 } else { 2.0 - t * 2.0 };
+
+        let pos = t * (self.stops.len() - 1) as f32;
+        let i = (pos.floor() as usize).min(self.stops.len() - 2);
+        let (from, to) = (self.stops[i], self.stops[i + 1]);
+        let f = pos - i as f32;
+
+        let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * f).round() as u8;
+        Colour::RGB(mix(from.0, to.0), mix(from.1, to.1), mix(from.2, to.2))
+    }
+}
+
+// how the lines of a block are coloured
+#[derive(Clone, Copy)]
+pub enum Paint<'a> {
+    Solid(Style),
+    Gradient { gradient: &'a Gradient, style: Style, mode: ColorMode, by_row: bool, phase: f32 },
+}
+
+impl Paint<'_> {
+    // the style of the character at `col` and `row` in a block of `size` width and height
+    pub fn style_at(&self, c: char, col: usize, row: usize, size: (usize, usize)) -> Style {
+        let (gradient, style, mode, by_row, phase) = match *self {
+            Paint::Solid(style) => return style,
+            Paint::Gradient { gradient, style, mode, by_row, phase } => (gradient, style, mode, by_row, phase),
+        };
+
+        // spaces only need the background, if there is one
+        if c == ' ' {
+            return style;
+        }
+
+        let t = |i: usize, len: usize| if len > 1 { i as f32 / (len - 1) as f32 } else { 0.0 };
+        let t = if by_row { t(row, size.1) } else { t(col, size.0) };
+        style.fg(mode.colour(gradient.colour_at(t, phase)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gradient(stops: &[(u8, u8, u8)]) -> Gradient {
+        Gradient { stops: stops.to_vec() }
+    }
+
+    #[test]
+    fn blends_between_the_stops() {
+        let g = gradient(&[(0, 0, 0), (200, 100, 0)]);
+        assert_eq!(g.colour_at(0.0, 0.0), Colour::RGB(0, 0, 0));
+        assert_eq!(g.colour_at(0.5, 0.0), Colour::RGB(100, 50, 0));
+        assert_eq!(g.colour_at(1.0, 0.0), Colour::RGB(200, 100, 0));
+
+        let g = gradient(&[(0, 0, 0), (100, 0, 0), (100, 100, 0)]);
+        assert_eq!(g.colour_at(0.5, 0.0), Colour::RGB(100, 0, 0));
+        assert_eq!(g.colour_at(0.75, 0.0), Colour::RGB(100, 50, 0));
+    }
+
+    #[test]
+    fn phase_goes_back_and_forth() {
+        let g = gradient(&[(0, 0, 0), (200, 0, 0)]);
+        assert_eq!(g.colour_at(0.0, 0.5), Colour::RGB(200, 0, 0));
+        assert_eq!(g.colour_at(1.0, 0.5), Colour::RGB(0, 0, 0));
+        assert_eq!(g.colour_at(0.0, 1.0), Colour::RGB(0, 0, 0));
+    }
+
+    #[test]
+    fn paints_by_column_or_row() {
+        let g = gradient(&[(0, 0, 0), (200, 0, 0)]);
+        let paint = |by_row| Paint::Gradient {
+            gradient: &g,
+            style: Style::new(),
+            mode: ColorMode::TrueColor,
+            by_row,
+            phase: 0.0,
+        };
+
+        assert_eq!(paint(false).style_at('x', 4, 0, (5, 3)), Style::new().fg(Colour::RGB(200, 0, 0)));
+        assert_eq!(paint(true).style_at('x', 4, 0, (5, 3)), Style::new().fg(Colour::RGB(0, 0, 0)));
+        assert_eq!(paint(true).style_at('x', 0, 2, (5, 3)), Style::new().fg(Colour::RGB(200, 0, 0)));
+        assert_eq!(paint(false).style_at(' ', 4, 0, (5, 3)), Style::new());
+        assert_eq!(Paint::Solid(Style::new().bold()).style_at('x', 4, 0, (5, 3)), Style::new().bold());
+    }
+
+    #[test]
+    fn splits_stops() {
+        assert_eq!(split_stops("red,#00c0ff"), ["red", "#00c0ff"]);
+        assert_eq!(split_stops("hsl(330, 100%, 50%),blue"), ["hsl(330, 100%, 50%)", "blue"]);
+        assert_eq!(split_stops("255,0,128;0,192,255"), ["255,0,128", "0,192,255"]);
+        assert_eq!(split_stops("red; blue; green"), ["red", " blue", " green"]);
+    }
+
+    #[test]
+    fn parses_any_color_as_a_stop() {
+        let g = Gradient::parse("hsl(0, 100%, 50%),#0000ff").unwrap();
+        assert_eq!(g.stops, [(255, 0, 0), (0, 0, 255)]);
+        let g = Gradient::parse("255,0,128; 0,192,255").unwrap();
+        assert_eq!(g.stops, [(255, 0, 128), (0, 192, 255)]);
+        assert!(Gradient::parse("255,0,128,0,192,255").is_err());
+    }
+
+    #[test]
+    fn needs_two_stops() {
+        assert!(Gradient::parse("red,blue").is_ok());
+        assert!(Gradient::parse("red").is_err());
+        assert!(Gradient::parse("red,nope").is_err());
+    }
+}
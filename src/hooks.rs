@@ -0,0 +1,87 @@
+// Shell commands that run when the countdown drops below a number of seconds, e.g. when it reaches zero.
+use std::{process::Command, thread};
+
+use crate::{AppEvent, Tx};
+
+pub struct Hook {
+    below: i32,
+    command: String,
+    armed: bool,
+}
+
+impl Hook {
+    pub fn new(below: i32, command: String) -> Self {
+        Self { below, command, armed: false }
+    }
+
+    // a hook only fires when the timer goes from above its threshold to below it,
+    // adding time back onto the timer arms it again
+    pub fn check(&mut self, remaining: i32) -> bool {
+        if remaining >= self.below {
+            self.armed = true;
+            return false;
+        }
+
+        let fire = self.armed;
+        self.armed = false;
+        fire
+    }
+
+    // runs the command on its own thread, failures are sent back to be shown on screen
+    pub fn run(&self, remaining: i32, formatted: String, message: &str, tx: Tx) {
+        let mut command = if cfg!(windows) { Command::new("cmd") } else { Command::new("sh") };
+        command
+            .arg(if cfg!(windows) { "/C" } else { "-c" })
+            .arg(&self.command)
+            .env("AFK_REMAINING", remaining.to_string())
+            .env("AFK_REMAINING_FORMATTED", formatted)
+            .env("AFK_MESSAGE", message);
+
+        let name = self.command.clone();
+        thread::spawn(move || {
+            let error = match command.output() {
+                Ok(output) if output.status.success() => return,
+                Ok(output) => {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    match stderr.lines().find(|l| !l.trim().is_empty()) {
+                        Some(line) => format!("`{name}` failed ({}): {}", output.status, line.trim()),
+                        None => format!("`{name}` failed ({})", output.status),
+                    }
+                }
+                Err(e) => format!("`{name}` could not be started: {e}"),
+            };
+            let _ = tx.send(AppEvent::CommandFailed(error));
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fires_once_when_crossing_below() {
+        let mut hook = Hook::new(30, String::new());
+        assert!(!hook.check(31));
+        assert!(!hook.check(30));
+        assert!(hook.check(29));
+        assert!(!hook.check(28));
+        assert!(!hook.check(0));
+    }
+
+    #[test]
+    fn does_not_fire_when_starting_below() {
+        let mut hook = Hook::new(30, String::new());
+        assert!(!hook.check(10));
+        assert!(!hook.check(9));
+    }
+
+    #[test]
+    fn adding_time_arms_it_again() {
+        let mut hook = Hook::new(1, String::new());
+        assert!(!hook.check(1));
+        assert!(hook.check(0));
+        assert!(!hook.check(60));
+        assert!(hook.check(0));
+    }
+}
@@ -0,0 +1,68 @@
+// Rounded phrases instead of digits, for when a ticking clock is more precise than anyone needs.
+pub fn phrase(total_sec: i32) -> String {
+    match total_sec {
+        // a little over is still any second now
+        -59..=59 => "any second now".to_string(),
+        60.. => format!("back in about {}", about(total_sec)),
+        _ => format!("running {} late", about(-total_sec)),
+    }
+}
+
+// a minute or more, rounded more coarsely the longer it is
+fn about(total_sec: i32) -> String {
+    let minutes = (total_sec + 30) / 60;
+
+    match minutes {
+        ..=1 => "a minute".to_string(),
+        2..=9 => format!("{minutes} minutes"),
+        10..=52 => format!("{} minutes", (minutes + 2) / 5 * 5),
+        _ => {
+            // to the nearest half hour
+            let halves = (minutes + 15) / 30;
+            match halves {
+                ..=2 => "an hour".to_string(),
+                _ if halves % 2 == 0 => format!("{} hours", halves / 2),
+                _ => format!("{}.5 hours", halves / 2),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn close_to_zero() {
+        assert_eq!(phrase(59), "any second now");
+        assert_eq!(phrase(0), "any second now");
+        assert_eq!(phrase(-59), "any second now");
+    }
+
+    #[test]
+    fn minutes() {
+        assert_eq!(phrase(60), "back in about a minute");
+        assert_eq!(phrase(89), "back in about a minute");
+        assert_eq!(phrase(90), "back in about 2 minutes");
+        assert_eq!(phrase(9 * 60), "back in about 9 minutes");
+        assert_eq!(phrase(12 * 60), "back in about 10 minutes");
+        assert_eq!(phrase(13 * 60), "back in about 15 minutes");
+        assert_eq!(phrase(52 * 60), "back in about 50 minutes");
+    }
+
+    #[test]
+    fn hours() {
+        assert_eq!(phrase(53 * 60), "back in about an hour");
+        assert_eq!(phrase(74 * 60), "back in about an hour");
+        assert_eq!(phrase(75 * 60), "back in about 1.5 hours");
+        assert_eq!(phrase(2 * 3600), "back in about 2 hours");
+        assert_eq!(phrase(150 * 60), "back in about 2.5 hours");
+    }
+
+    #[test]
+    fn overtime() {
+        assert_eq!(phrase(-60), "running a minute late");
+        assert_eq!(phrase(-20 * 60), "running 20 minutes late");
+        assert_eq!(phrase(-3 * 3600), "running 3 hours late");
+    }
+}
@@ -0,0 +1,127 @@
+// Places the message and the timer on the screen.
+//
+// Every block is aligned horizontally on its own, relative to the terminal. Blocks that share a vertical
+// alignment are stacked in the order they are given, and the stack is aligned vertically as a whole.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum HAlign {
+    Left,
+    Center,
+    Right,
+}
+
+impl HAlign {
+    pub fn parse(align: &str) -> Option<Self> {
+        match align.to_lowercase().as_ref() {
+            "left" => Some(Self::Left),
+            "center" | "centre" => Some(Self::Center),
+            "right" => Some(Self::Right),
+            _ => None,
+        }
+    }
+
+    // where something `width` wide goes inside a block that is `outer` wide
+    pub fn offset(self, width: u16, outer: u16) -> u16 {
+        match self {
+            Self::Left => 0,
+            Self::Center => outer.saturating_sub(width) / 2,
+            Self::Right => outer.saturating_sub(width),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum VAlign {
+    Top,
+    Middle,
+    Bottom,
+}
+
+impl VAlign {
+    pub fn parse(align: &str) -> Option<Self> {
+        match align.to_lowercase().as_ref() {
+            "top" => Some(Self::Top),
+            "middle" | "center" | "centre" => Some(Self::Middle),
+            "bottom" => Some(Self::Bottom),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Block {
+    pub width: u16,
+    pub height: u16,
+    // horizontal padding from the edge the block is aligned to, and the blank lines above the block.
+    // centered blocks ignore the horizontal padding
+    pub padding: (u16, u16),
+    pub align: HAlign,
+    pub valign: VAlign,
+}
+
+// returns the top left corner of each block
+pub fn arrange(size: (u16, u16), blocks: &[Block]) -> Vec<(u16, u16)> {
+    let (width, height) = size;
+
+    let x = |block: &Block| match block.align {
+        HAlign::Left => block.padding.0,
+        HAlign::Center => width.saturating_sub(block.width) / 2,
+        HAlign::Right => width.saturating_sub(block.width).saturating_sub(block.padding.0),
+    };
+
+    let stack_height =
+        |valign: VAlign| -> u16 { blocks.iter().filter(|b| b.valign == valign).map(|b| b.padding.1 + b.height).sum() };
+
+    let mut tops = [VAlign::Top, VAlign::Middle, VAlign::Bottom].map(|valign| match valign {
+        VAlign::Top => 0,
+        VAlign::Middle => height.saturating_sub(stack_height(valign)) / 2,
+        VAlign::Bottom => height.saturating_sub(stack_height(valign)),
+    });
+
+    blocks
+        .iter()
+        .map(|block| {
+            let top = &mut tops[block.valign as usize];
+            let y = *top + block.padding.1;
+            *top = y + block.height;
+            (x(block), y)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(width: u16, height: u16, align: HAlign, valign: VAlign) -> Block {
+        Block { width, height, padding: (0, 0), align, valign }
+    }
+
+    #[test]
+    fn parse() {
+        assert!(HAlign::parse("Centre") == Some(HAlign::Center));
+        assert!(HAlign::parse("middle").is_none());
+        assert!(VAlign::parse("center") == Some(VAlign::Middle));
+    }
+
+    #[test]
+    fn stacks_blocks_with_the_same_valign() {
+        let blocks = [block(10, 2, HAlign::Center, VAlign::Middle), block(20, 4, HAlign::Center, VAlign::Middle)];
+        assert_eq!(arrange((80, 24), &blocks), vec![(35, 9), (30, 11)]);
+    }
+
+    #[test]
+    fn aligns_each_block_on_its_own() {
+        let mut message = block(10, 2, HAlign::Left, VAlign::Top);
+        message.padding = (3, 1);
+        let mut timer = block(20, 4, HAlign::Right, VAlign::Bottom);
+        timer.padding = (2, 0);
+        assert_eq!(arrange((80, 24), &[message, timer]), vec![(3, 1), (58, 20)]);
+    }
+
+    #[test]
+    fn offset() {
+        assert_eq!(HAlign::Center.offset(4, 10), 3);
+        assert_eq!(HAlign::Right.offset(4, 10), 6);
+        assert_eq!(HAlign::Right.offset(12, 10), 0);
+    }
+}
@@ -1,67 +1,152 @@
+mod clock;
+mod color;
+mod config;
+#[cfg(unix)]
+mod control;
+mod countdown;
+mod duration;
+mod font;
+mod format;
+mod gradient;
+mod hooks;
+mod human;
+mod layout;
+mod output;
+mod progress;
+mod screen;
+
 use std::{
     env::args,
     error::Error,
-    io::{Stdout, Write},
-    iter,
-    sync::mpsc::{self, Sender},
+    fs,
+    iter::{self, Peekable},
+    path::PathBuf,
+    slice,
+    sync::mpsc::{self, RecvTimeoutError, Sender},
     thread,
     time::{Duration, Instant},
 };
 
 use ansi_term::{Colour, Style};
-use crossterm::{cursor::MoveTo, style::Print, terminal, QueueableCommand};
-use figglebit::{cleanup, init, parse, Renderer};
+use chrono::Local;
+use clock::Clock;
+use color::{parse_color, ColorMode};
+use countdown::Countdown;
+use crossterm::terminal;
+use figglebit::{cleanup, init, Renderer};
+use format::Format;
+use gradient::{Gradient, Paint};
+use hooks::Hook;
+use layout::{Block, HAlign, VAlign};
+use output::OutputFile;
+use progress::{Fill, Progress};
+use screen::Screen;
 
 type Tx = Sender<AppEvent>;
 
+const PAUSED: &str = "PAUSED";
+const EDIT_PROMPT: &str = "Message: ";
+// how long errors stay on the bottom line of the screen
+const STATUS_DURATION: Duration = Duration::from_secs(10);
+// how often an animated gradient is drawn
+const ANIMATION_FRAME: Duration = Duration::from_millis(50);
+// the most often a fraction of a second is drawn, hundredths change faster than anyone can read them
+const FRACTION_FRAME: Duration = Duration::from_millis(25);
+
 enum AppEvent {
-    Tick,
     Quit,
     ModifyTimer(i32),
+    Pause,
+    Reset,
+    Restart,
+    CommandFailed(String),
+    // sent by the control socket
+    #[cfg(unix)]
+    SetTimer(i32),
+    #[cfg(unix)]
+    SetPaused(bool),
+    #[cfg(unix)]
+    SetMessage(String),
+    #[cfg(unix)]
+    Status(Sender<String>),
+    Edit(Edit),
+    Resize,
+}
+
+enum Edit {
+    Start,
+    Char(char),
+    Backspace,
+    Submit,
+    Cancel,
 }
 
 fn events(tx: Tx) {
     use crossterm::event::{self, Event as CEvent, KeyCode, KeyEvent, KeyModifiers as KeyMods};
 
-    thread::spawn(move || loop {
-        if let Ok(CEvent::Key(KeyEvent { code, modifiers })) = event::read() {
-            match code {
-                KeyCode::Esc => drop(tx.send(AppEvent::Quit)),
-                KeyCode::Char('c') if modifiers.contains(KeyMods::CONTROL) => drop(tx.send(AppEvent::Quit)),
-                KeyCode::Char('q') => drop(tx.send(AppEvent::Quit)),
-                KeyCode::Char('s') => drop(tx.send(AppEvent::ModifyTimer(-1))),
-                KeyCode::Char('S') => drop(tx.send(AppEvent::ModifyTimer(1))),
-                KeyCode::Char('m') => drop(tx.send(AppEvent::ModifyTimer(-60))),
-                KeyCode::Char('M') => drop(tx.send(AppEvent::ModifyTimer(60))),
-                KeyCode::Char('h') => drop(tx.send(AppEvent::ModifyTimer(-3600))),
-                KeyCode::Char('H') => drop(tx.send(AppEvent::ModifyTimer(3600))),
+    thread::spawn(move || {
+        // while the message is being edited keys are text rather than keybinds
+        let mut editing = false;
+
+        loop {
+            match event::read() {
+                Ok(CEvent::Resize(..)) => drop(tx.send(AppEvent::Resize)),
+                Ok(CEvent::Key(KeyEvent { code, modifiers })) => match code {
+                    KeyCode::Char('c') if modifiers.contains(KeyMods::CONTROL) => drop(tx.send(AppEvent::Quit)),
+                    KeyCode::Esc if editing => {
+                        editing = false;
+                        drop(tx.send(AppEvent::Edit(Edit::Cancel)));
+                    }
+                    KeyCode::Enter if editing => {
+                        editing = false;
+                        drop(tx.send(AppEvent::Edit(Edit::Submit)));
+                    }
+                    KeyCode::Backspace if editing => drop(tx.send(AppEvent::Edit(Edit::Backspace))),
+                    KeyCode::Char(c) if editing => drop(tx.send(AppEvent::Edit(Edit::Char(c)))),
+                    _ if editing => {}
+                    KeyCode::Esc => drop(tx.send(AppEvent::Quit)),
+                    KeyCode::Char('q') => drop(tx.send(AppEvent::Quit)),
+                    KeyCode::Char('s') => drop(tx.send(AppEvent::ModifyTimer(-1))),
+                    KeyCode::Char('S') => drop(tx.send(AppEvent::ModifyTimer(1))),
+                    KeyCode::Char('m') => drop(tx.send(AppEvent::ModifyTimer(-60))),
+                    KeyCode::Char('M') => drop(tx.send(AppEvent::ModifyTimer(60))),
+                    KeyCode::Char('h') => drop(tx.send(AppEvent::ModifyTimer(-3600))),
+                    KeyCode::Char('H') => drop(tx.send(AppEvent::ModifyTimer(3600))),
+                    KeyCode::Char(' ') | KeyCode::Char('p') => drop(tx.send(AppEvent::Pause)),
+                    KeyCode::Char('r') => drop(tx.send(AppEvent::Reset)),
+                    KeyCode::Char('R') => drop(tx.send(AppEvent::Restart)),
+                    KeyCode::Char('e') => {
+                        editing = true;
+                        drop(tx.send(AppEvent::Edit(Edit::Start)));
+                    }
+                    _ => {}
+                },
                 _ => {}
             }
         }
     });
 }
 
-fn tick_timer(tx: Tx) {
-    thread::spawn(move || loop {
-        thread::sleep(Duration::from_secs(1));
-        let _ = tx.send(AppEvent::Tick);
-    });
-}
-
 fn format_time(mut total_sec: i32, show_zeroes: bool) -> String {
     let is_less_than_zero = total_sec < 0;
     if is_less_than_zero {
         total_sec *= -1;
     }
-    let hours = total_sec / 60 / 60;
-    let minutes = total_sec / 60 - (hours * 60);
-    let seconds = total_sec - minutes * 60 - hours * 60 * 60;
+    let days = total_sec / 60 / 60 / 24;
+    let hours = total_sec / 60 / 60 - (days * 24);
+    let minutes = total_sec / 60 - (days * 24 * 60) - (hours * 60);
+    let seconds = total_sec % 60;
 
     format!(
-        "{}{}{}{:0>2}",
+        "{}{}{}{}{:0>2}",
         if is_less_than_zero { "-" } else { "" },
-        if hours.eq(&0) && !show_zeroes { "".to_string() } else { format!("{:0>2}:", hours) },
-        if hours.eq(&0) && minutes.eq(&0) && !show_zeroes { "".to_string() } else { format!("{:0>2}:", minutes) },
+        if days.eq(&0) { "".to_string() } else { format!("{}:", days) },
+        if days.eq(&0) && hours.eq(&0) && !show_zeroes { "".to_string() } else { format!("{:0>2}:", hours) },
+        if days.eq(&0) && hours.eq(&0) && minutes.eq(&0) && !show_zeroes {
+            "".to_string()
+        } else {
+            format!("{:0>2}:", minutes)
+        },
         seconds
     )
 }
@@ -71,16 +156,49 @@ struct AfkConfig {
     hours: i32,
     minutes: i32,
     seconds: i32,
-    style: Style,
+    message_style: Style,
+    timer_style: Style,
+    background: Option<Colour>,
+    color_mode: ColorMode,
+    // the timer style once less than this many seconds are left, and once it goes negative
+    warnings: Vec<(i32, Style)>,
+    overtime: Option<Style>,
+    fade: Option<Gradient>,
+    progress: Option<Progress>,
     words: String,
     blink_timer: Instant,
     blink_rate: u64, // in ms
     is_blinking: bool,
     show_zeroes: bool,
+    format: Option<Format>,
+    // phrases instead of digits, in the message font or as plain text
+    human: bool,
+    human_font: bool,
+    clock: Option<Clock>,
     use_font: bool,
     message_padding: (u16, u16),
     timer_padding: (u16, u16),
-    center_timer: bool,
+    message_align: HAlign,
+    message_valign: VAlign,
+    timer_align: HAlign,
+    timer_valign: VAlign,
+    timer_first: bool,
+    gradient: Option<Gradient>,
+    gradient_by_row: bool,
+    animate: bool,
+    timer_font: Renderer,
+    fraction_font: Renderer,
+    // digits after the seconds
+    precision: u32,
+    message_font: Renderer,
+    hooks: Vec<Hook>,
+    output_file: Option<OutputFile>,
+    output_message: bool,
+    control_socket: Option<PathBuf>,
+    messages: Vec<String>,
+    message_index: usize,
+    rotate_every: u64, // in seconds
+    rotated_at: Instant,
 }
 
 impl Default for AfkConfig {
@@ -90,24 +208,138 @@ impl Default for AfkConfig {
             hours: 0,
             minutes: 0,
             seconds: 0,
-            style: Style::new().fg(Colour::White),
+            message_style: Style::new().fg(Colour::White),
+            timer_style: Style::new().fg(Colour::White),
+            background: None,
+            color_mode: ColorMode::detect(),
+            warnings: Vec::new(),
+            overtime: None,
+            fade: None,
+            progress: None,
             words: "".to_string(),
             blink_timer: Instant::now(),
             blink_rate: 500,
             is_blinking: false,
             show_zeroes: true,
+            format: None,
+            human: false,
+            human_font: false,
+            clock: None,
             use_font: false,
             // Default behavior of legacy afk
             message_padding: (2, 2),
             timer_padding: (0, 2),
-            center_timer: false,
+            message_align: HAlign::Left,
+            message_valign: VAlign::Top,
+            timer_align: HAlign::Left,
+            timer_valign: VAlign::Top,
+            timer_first: false,
+            gradient: None,
+            gradient_by_row: false,
+            animate: false,
+            timer_font: font::bundled("Ghost"),
+            fraction_font: font::bundled("Cosmike"),
+            precision: 0,
+            message_font: font::bundled("Big"),
+            hooks: Vec::new(),
+            output_file: None,
+            output_message: false,
+            control_socket: None,
+            messages: Vec::new(),
+            message_index: 0,
+            rotate_every: 30,
+            rotated_at: Instant::now(),
         }
     }
 }
 
 impl AfkConfig {
+    // the duration the timer started with
+    fn total_seconds(&self) -> i32 {
+        self.hours * 60 * 60 + self.minutes * 60 + self.seconds
+    }
+
+    fn format_time(&self, total_sec: i32) -> String {
+        match &self.format {
+            Some(format) => format.format(total_sec),
+            None => format_time(total_sec, self.show_zeroes),
+        }
+    }
+
+    fn reset_blinker(&mut self) {
+        self.is_blinking = false;
+        self.blink_timer = Instant::now();
+    }
+
+    // replaces the message being shown, which is also the one that comes back around when rotating.
+    // the new message gets a full turn before the next one is shown
+    fn set_message(&mut self, words: String) {
+        if let Some(message) = self.messages.get_mut(self.message_index) {
+            *message = words.clone();
+        }
+        self.words = words;
+        self.rotated_at = Instant::now();
+    }
+
+    // returns true when it is time for the next message
+    fn rotate_message(&mut self) -> bool {
+        if self.messages.len() < 2 || self.rotated_at.elapsed() < Duration::from_secs(self.rotate_every) {
+            return false;
+        }
+
+        self.message_index = (self.message_index + 1) % self.messages.len();
+        self.words = self.messages[self.message_index].clone();
+        self.rotated_at = Instant::now();
+        true
+    }
+
+    // animated gradients go through all their colours every few seconds
+    fn paint(&self, style: Style, elapsed: Duration) -> Paint<'_> {
+        match &self.gradient {
+            Some(gradient) if self.color_mode != ColorMode::None => Paint::Gradient {
+                gradient,
+                style,
+                mode: self.color_mode,
+                by_row: self.gradient_by_row,
+                phase: if self.animate { elapsed.as_secs_f32() / 4.0 } else { 0.0 },
+            },
+            _ => Paint::Solid(style),
+        }
+    }
+
+    // the style the timer changes to as it runs out, if any. the smallest threshold that was passed wins
+    fn warning_style(&self, remaining: i32) -> Option<Style> {
+        if remaining < 0 && self.overtime.is_some() {
+            return self.overtime;
+        }
+
+        let warning = self.warnings.iter().filter(|(below, _)| remaining < *below).min_by_key(|(below, _)| *below);
+        if let Some((_, style)) = warning {
+            return Some(*style);
+        }
+
+        let total = self.total_seconds();
+        match &self.fade {
+            Some(fade) if total > 0 => {
+                let left = remaining.clamp(0, total) as f32 / total as f32;
+                Some(self.timer_style.fg(fade.colour_at(1.0 - left, 0.0)))
+            }
+            _ => None,
+        }
+    }
+
+    // anything printed has to carry the background, or it would punch holes in it,
+    // and can only use the colors the terminal supports
+    fn for_terminal(&self, style: Style) -> Style {
+        let style = match (self.background, style.background) {
+            (Some(background), None) => style.on(background),
+            _ => style,
+        };
+        self.color_mode.style(style)
+    }
+
     fn flip_blinker(&mut self) {
-        if self.blink_timer.elapsed() >= Duration::from_millis(self.blink_rate) {
+        if self.blink_rate > 0 && self.blink_timer.elapsed() >= Duration::from_millis(self.blink_rate) {
             self.is_blinking = !self.is_blinking;
             self.blink_timer = Instant::now();
         }
@@ -117,12 +349,17 @@ impl AfkConfig {
 fn show_help() {
     let help = include_str!("../README.md");
 
-    println!("{}", Style::new().fg(Colour::Blue).bold().paint(help));
+    println!("{}", ColorMode::detect().style(Style::new().fg(Colour::Blue).bold()).paint(help));
+}
+
+// errors can come before the config is known, so they only go by the environment
+fn error_style() -> Style {
+    ColorMode::detect().style(Style::new().fg(Colour::Red).bold())
 }
 
 macro_rules! show_error {
     ($error:expr) => {{
-        println!("{}", Style::new().fg(Colour::Red).bold().paint($error));
+        println!("{}", error_style().paint($error));
         return None;
     }};
 }
@@ -134,143 +371,476 @@ fn parse_args(args: &[String]) -> Option<AfkConfig> {
 
     let mut config = AfkConfig::default();
 
+    // the config file and profile have to be known up front, as the cli flags are applied on top of them
+    let mut profile = None;
+    let mut config_path = None;
+    let mut time_flag_given = false;
+    let mut args_iter = args.iter();
+    while let Some(arg) = args_iter.next() {
+        match arg.to_lowercase().as_ref() {
+            "-h" | "-m" | "-s" | "-d" | "--duration" | "--until" => time_flag_given = true,
+            "--profile" => match args_iter.next() {
+                Some(p) => profile = Some(p.as_str()),
+                None => show_error!(&format!("Missing profile name after {arg}.")),
+            },
+            "--config" => match args_iter.next() {
+                Some(p) => config_path = Some(PathBuf::from(p)),
+                None => show_error!(&format!("Missing path after {arg}.")),
+            },
+            _ => {}
+        }
+    }
+
+    if let Err(e) = config::load(&mut config, config_path, profile) {
+        show_error!(&e);
+    }
+
     let mut args = args.iter().peekable();
+    let mut words_from_args = false;
+    let mut time_from_args = false;
+    let mut duration_from_args = false;
+    let mut lists_from_args = Vec::new();
 
     while let Some(arg) = args.next() {
         match arg.to_lowercase().as_ref() {
             "--help" => return None,
-            "-k" => config.allow_negative = true,
-            "-h" | "-m" | "-s" => match args.next() {
-                Some(t) => match t.parse() {
-                    Ok(t) => match arg.to_lowercase().as_ref() {
-                        "-h" => config.hours = t,
-                        "-m" => config.minutes = t,
-                        "-s" => config.seconds = t,
-                        _ => {}
-                    },
-                    Err(_) => show_error!(&format!("Cannot parse number after {}.", arg)),
-                },
-                None => show_error!(&format!("Missing number after {}.", arg)),
-            },
-            "-c" => {
-                config.style = match args.next() {
-                    Some(c) => match parse_color(c) {
-                        Some(c) => Style::new().fg(c).bold(),
-                        None => show_error!(&format!("Unknown color after {}.", arg)),
-                    },
-                    None => show_error!(&format!("Missing color after {}.", arg)),
+            "--profile" | "--config" => drop(args.next()),
+            flag => {
+                // the first positional that looks like a duration is the duration, e.g. afk 10 "BRB",
+                // unless the time is given with a flag, so afk -m 5 404 shows 404 for five minutes
+                let is_duration = !duration_from_args && !time_flag_given && duration::looks_like_duration(arg);
+
+                // a duration on the command line replaces the one from the config file rather than adding to it
+                if (is_duration || matches!(flag, "-h" | "-m" | "-s" | "-d" | "--duration" | "--until"))
+                    && !time_from_args
+                {
+                    config.hours = 0;
+                    config.minutes = 0;
+                    config.seconds = 0;
+                    time_from_args = true;
                 }
-            }
-            "-0" => config.show_zeroes = false,
-            "-f" => config.use_font = true,
-            "-z" => config.center_timer = true,
-            "-p" => {
-                config.message_padding = match (args.next(), args.peek()) {
-                    (Some(x), Some(y)) if !y.starts_with('-') => match (x.parse(), y.parse()) {
-                        (Ok(x), Ok(y)) => (x, y),
-                        (Err(_), _) => show_error!(&format!("Cannot parse number {x} after {arg}.")),
-                        (_, _) => show_error!(&format!("Cannot parse number {y} after {arg}.")),
-                    },
-                    (Some(p), _) => match p.parse() {
-                        Ok(p) => (p, p),
-                        Err(_) => show_error!(&format!("Cannot parse number {p} after {arg}.")),
-                    },
-                    _ => show_error!(&format!("Missing padding after {arg}.")),
+
+                // same for the settings that can be given more than once, the first one on the command line
+                // replaces the ones from the config file
+                let list = match flag {
+                    "--message" | "--messages-file" => Some("messages"),
+                    "--warn" => Some("warn"),
+                    "--on-end" | "--on-tick-below" => Some("hooks"),
+                    _ => None,
+                };
+                if let Some(list) = list.filter(|list| !lists_from_args.contains(list)) {
+                    match list {
+                        "messages" if !words_from_args => {
+                            config.messages.clear();
+                            config.words.clear();
+                        }
+                        "messages" => config.messages.clear(),
+                        "warn" => config.warnings.clear(),
+                        _ => config.hooks.clear(),
+                    }
+                    lists_from_args.push(list);
                 }
-            }
-            "-t" => {
-                config.timer_padding = match (args.next(), args.peek()) {
-                    (Some(x), Some(y)) if !y.starts_with('-') => match (x.parse(), y.parse()) {
-                        (Ok(x), Ok(y)) => (x, y),
-                        (Err(_), _) => show_error!(&format!("Cannot parse number {x} after {arg}.")),
-                        (_, _) => show_error!(&format!("Cannot parse number {y} after {arg}.")),
-                    },
-                    (Some(p), _) => match p.parse() {
-                        Ok(p) => (p, p),
-                        Err(_) => show_error!(&format!("Cannout parse number {p} after {arg}.")),
-                    },
-                    _ => show_error!(&format!("Missing padding after {arg}.")),
+
+                if is_duration {
+                    match duration::parse_duration(arg) {
+                        Ok(hms) => (config.hours, config.minutes, config.seconds) = hms,
+                        Err(e) => show_error!(&e),
+                    }
+                    duration_from_args = true;
+                    continue;
                 }
-            }
-            _ => {
-                // takes the first unquoted word or "quoted string of words" ignoring any words, strings, or invalid commands after
-                if config.words.is_empty() {
-                    config.words = arg.to_string();
+
+                match parse_flag(&mut config, arg, &mut args) {
+                    Ok(true) => {}
+                    // takes the first unquoted word or "quoted string of words" ignoring any words, strings, or invalid commands after
+                    Ok(false) => {
+                        if !words_from_args {
+                            if !lists_from_args.contains(&"messages") {
+                                config.messages.clear();
+                                lists_from_args.push("messages");
+                            }
+                            config.words = arg.to_string();
+                            words_from_args = true;
+                        }
+                    }
+                    Err(e) => show_error!(&e),
                 }
             }
         }
     }
 
+    // the message given without a flag is the first one to be shown
+    if !config.words.is_empty() && !config.messages.is_empty() {
+        config.messages.insert(0, config.words.clone());
+    }
+    if let Some(first) = config.messages.first() {
+        config.words = first.clone();
+    }
+
+    // -h, -m and -s are only checked one by one
+    match duration::to_seconds(config.hours, config.minutes, config.seconds) {
+        None => show_error!("The time is too large."),
+        Some(..0) => show_error!("The time cannot be negative."),
+        Some(_) => {}
+    }
+
     // prefer some time to act against, unless allow_negative, which is basically just a stopwatch
-    if config.hours.eq(&0) && config.minutes.eq(&0) && config.seconds.eq(&0) && !config.allow_negative {
+    if config.hours.eq(&0)
+        && config.minutes.eq(&0)
+        && config.seconds.eq(&0)
+        && !config.allow_negative
+        && config.clock.is_none()
+    {
         show_error!("Please specifiy some time or -k for stopwatch.");
     }
 
     Some(config)
 }
 
-fn parse_color(color: &str) -> Option<Colour> {
-    let color = match color.to_lowercase().as_ref() {
-        "black" => Colour::Black,
-        "red" => Colour::Red,
-        "green" => Colour::Green,
-        "yellow" => Colour::Yellow,
-        "blue" => Colour::Blue,
-        "purple" => Colour::Purple,
-        "cyan" => Colour::Cyan,
-        "white" => Colour::White,
-        _ => {
-            // Check for RGB color value formatted as 42,42,42 or "42 42 42"
-            let rgb = color.contains(&[',', ' '][..]).then(|| {
-                color.split(&[',', ' '][..]).map(str::parse::<u8>).filter_map(Result::ok).collect::<Vec<u8>>()
-            })?;
-
-            if rgb.len() != 3 {
-                show_error!("RGB values should have 3 numbers separated by commas.");
+// applies a single flag and its values to the config.
+// returns false if `arg` is not a flag, so the caller can decide what to do with it
+fn parse_flag<'a, I>(config: &mut AfkConfig, arg: &str, args: &mut Peekable<I>) -> Result<bool, String>
+where
+    I: Iterator<Item = &'a String>,
+{
+    match arg.to_lowercase().as_ref() {
+        "-k" => config.allow_negative = true,
+        "-h" | "-m" | "-s" => match args.next() {
+            Some(t) => match t.parse() {
+                Ok(t) => match arg.to_lowercase().as_ref() {
+                    "-h" => config.hours = t,
+                    "-m" => config.minutes = t,
+                    "-s" => config.seconds = t,
+                    _ => {}
+                },
+                Err(_) => return Err(format!("Cannot parse number after {}.", arg)),
+            },
+            None => return Err(format!("Missing number after {}.", arg)),
+        },
+        "-d" | "--duration" => match args.next() {
+            Some(d) => (config.hours, config.minutes, config.seconds) = duration::parse_duration(d)?,
+            None => return Err(format!("Missing duration after {arg}.")),
+        },
+        "--until" => match args.next() {
+            Some(until) => {
+                config.hours = 0;
+                config.minutes = 0;
+                config.seconds = duration::parse_until(until, Local::now())?;
             }
-
-            Colour::RGB(rgb[0], rgb[1], rgb[2])
+            None => return Err(format!("Missing time after {arg}.")),
+        },
+        "-c" => {
+            let style = match args.next() {
+                Some(c) => Style::new().fg(parse_color(c).map_err(|e| format!("{e} after {arg}."))?).bold(),
+                None => return Err(format!("Missing color after {}.", arg)),
+            };
+            config.message_style = style;
+            config.timer_style = style;
         }
-    };
+        "--message-color" | "--timer-color" => {
+            let style = match args.next() {
+                Some(spec) => parse_style(spec).map_err(|e| format!("{e} after {arg}."))?,
+                None => return Err(format!("Missing color after {arg}.")),
+            };
+            match arg.to_lowercase().as_ref() {
+                "--message-color" => config.message_style = style,
+                _ => config.timer_style = style,
+            }
+        }
+        "--warn" => match args.next() {
+            Some(warn) => config
+                .warnings
+                .push(parse_warning(warn).map_err(|e| format!("{} after {arg}.", e.trim_end_matches('.')))?),
+            None => return Err(format!("Missing duration and color after {arg}.")),
+        },
+        "--overtime" => match args.next() {
+            Some(spec) => config.overtime = Some(parse_style(spec).map_err(|e| format!("{e} after {arg}."))?),
+            None => return Err(format!("Missing color after {arg}.")),
+        },
+        "--fade" => config.fade = Some(Gradient::fade()),
+        "--progress" => drop(config.progress.get_or_insert_with(Progress::default)),
+        "--progress-width" => match args.next() {
+            Some(width) => match width.parse() {
+                Ok(width @ 1..) => config.progress.get_or_insert_with(Progress::default).width = Some(width),
+                _ => return Err(format!("Cannot parse width {width} after {arg}.")),
+            },
+            None => return Err(format!("Missing width after {arg}.")),
+        },
+        "--progress-fill" => match args.next() {
+            Some(fill) => {
+                config.progress.get_or_insert_with(Progress::default).fill = Fill::parse(fill)
+                    .ok_or_else(|| format!("Unknown fill {fill} after {arg}, expected block, half or braille."))?
+            }
+            None => return Err(format!("Missing fill after {arg}.")),
+        },
+        "--progress-color" | "--progress-track-color" => {
+            let style = match args.next() {
+                Some(spec) => parse_style(spec).map_err(|e| format!("{e} after {arg}."))?,
+                None => return Err(format!("Missing color after {arg}.")),
+            };
+            let progress = config.progress.get_or_insert_with(Progress::default);
+            match arg.to_lowercase().as_ref() {
+                "--progress-color" => progress.style = Some(style),
+                _ => progress.track_style = Some(style),
+            }
+        }
+        "--progress-percent" => config.progress.get_or_insert_with(Progress::default).percent = true,
+        "--background" => match args.next() {
+            Some(c) => config.background = Some(parse_color(c).map_err(|e| format!("{e} after {arg}."))?),
+            None => return Err(format!("Missing color after {arg}.")),
+        },
+        "--color-mode" => match args.next() {
+            Some(mode) => {
+                config.color_mode = ColorMode::parse(mode).ok_or_else(|| {
+                    format!("Unknown color mode {mode} after {arg}, expected truecolor, 256, 16 or none.")
+                })?
+            }
+            None => return Err(format!("Missing color mode after {arg}.")),
+        },
+        "-0" => config.show_zeroes = false,
+        "--human" => config.human = true,
+        "--human-font" => {
+            config.human = true;
+            config.human_font = true;
+        }
+        "--clock" => drop(config.clock.get_or_insert_with(Clock::default)),
+        "--clock-12h" => config.clock.get_or_insert_with(Clock::default).twelve_hour = true,
+        "--clock-seconds" => config.clock.get_or_insert_with(Clock::default).seconds = true,
+        "--clock-date" => config.clock.get_or_insert_with(Clock::default).date = true,
+        "--format" => match args.next() {
+            Some(template) => config.format = Some(Format::parse(template).map_err(|e| format!("{e} after {arg}."))?),
+            None => return Err(format!("Missing format after {arg}.")),
+        },
+        "-f" => config.use_font = true,
+        "-z" => config.timer_align = HAlign::Center,
+        "--align" | "--message-align" | "--timer-align" => match args.next() {
+            Some(align) => {
+                let align = HAlign::parse(align)
+                    .ok_or_else(|| format!("Unknown alignment {align} after {arg}, expected left, center or right."))?;
+                let flag = arg.to_lowercase();
+                if flag != "--timer-align" {
+                    config.message_align = align;
+                }
+                if flag != "--message-align" {
+                    config.timer_align = align;
+                }
+            }
+            None => return Err(format!("Missing alignment after {arg}.")),
+        },
+        "--valign" | "--message-valign" | "--timer-valign" => match args.next() {
+            Some(valign) => {
+                let valign = VAlign::parse(valign).ok_or_else(|| {
+                    format!("Unknown alignment {valign} after {arg}, expected top, middle or bottom.")
+                })?;
+                let flag = arg.to_lowercase();
+                if flag != "--timer-valign" {
+                    config.message_valign = valign;
+                }
+                if flag != "--message-valign" {
+                    config.timer_valign = valign;
+                }
+            }
+            None => return Err(format!("Missing alignment after {arg}.")),
+        },
+        "--timer-first" => config.timer_first = true,
+        "--gradient" => match args.next() {
+            Some(gradient) => config.gradient = Some(Gradient::parse(gradient)?),
+            None => return Err(format!("Missing colors after {arg}.")),
+        },
+        "--rainbow" => config.gradient = Some(Gradient::rainbow()),
+        "--gradient-rows" => config.gradient_by_row = true,
+        "--animate" => config.animate = true,
+        "-p" => config.message_padding = parse_padding(arg, args)?,
+        "-t" => config.timer_padding = parse_padding(arg, args)?,
+        "--timer-font" => match args.next() {
+            Some(font) => config.timer_font = font::load(font)?,
+            None => return Err(format!("Missing font after {arg}.")),
+        },
+        "--fraction-font" => match args.next() {
+            Some(font) => config.fraction_font = font::load(font)?,
+            None => return Err(format!("Missing font after {arg}.")),
+        },
+        "--precision" => match args.next() {
+            Some(digits) => match digits.parse() {
+                Ok(digits @ 0..=2) => config.precision = digits,
+                _ => return Err(format!("Cannot parse precision {digits} after {arg}, expected 0, 1 or 2.")),
+            },
+            None => return Err(format!("Missing precision after {arg}.")),
+        },
+        "--message-font" => match args.next() {
+            Some(font) => {
+                config.message_font = font::load(font)?;
+                config.use_font = true;
+            }
+            None => return Err(format!("Missing font after {arg}.")),
+        },
+        "--on-end" => match args.next() {
+            Some(command) => config.hooks.push(Hook::new(1, command.to_string())),
+            None => return Err(format!("Missing command after {arg}.")),
+        },
+        "--on-tick-below" => match (args.next(), args.next()) {
+            (Some(below), Some(command)) => {
+                config.hooks.push(Hook::new(duration::parse_seconds(below)?, command.to_string()))
+            }
+            (Some(_), None) => return Err(format!("Missing command after {arg}.")),
+            (None, _) => return Err(format!("Missing seconds after {arg}.")),
+        },
+        "--output-file" => match args.next() {
+            Some(path) => config.output_file = Some(OutputFile::new(PathBuf::from(path))),
+            None => return Err(format!("Missing path after {arg}.")),
+        },
+        "--output-message" => config.output_message = true,
+        "--listen" | "--socket" if !cfg!(unix) => return Err(format!("{arg} is only supported on unix.")),
+        #[cfg(unix)]
+        "--listen" => config.control_socket = Some(control::default_path()),
+        "--socket" => match args.next() {
+            Some(path) => config.control_socket = Some(PathBuf::from(path)),
+            None => return Err(format!("Missing path after {arg}.")),
+        },
+        "--message" => match args.next() {
+            Some(message) => config.messages.push(message.to_string()),
+            None => return Err(format!("Missing message after {arg}.")),
+        },
+        "--messages-file" => match args.next() {
+            Some(path) => match fs::read_to_string(path) {
+                Ok(messages) => {
+                    config.messages.extend(messages.lines().filter(|l| !l.trim().is_empty()).map(str::to_string))
+                }
+                Err(e) => return Err(format!("Cannot read messages from {path}: {e}.")),
+            },
+            None => return Err(format!("Missing path after {arg}.")),
+        },
+        "--rotate" => match args.next() {
+            Some(every) => match duration::parse_seconds(every)? {
+                seconds @ 1.. => config.rotate_every = seconds as u64,
+                _ => return Err(format!("The time between messages after {arg} should be at least a second.")),
+            },
+            None => return Err(format!("Missing seconds after {arg}.")),
+        },
+        "--blink-rate" => match args.next() {
+            // 0 turns blinking off, anything faster than 50ms would only keep the terminal busy
+            Some(ms) => match ms.parse() {
+                Ok(ms @ (0 | 50..)) => config.blink_rate = ms,
+                Ok(_) => return Err(format!("The blink rate after {arg} should be 0 or at least 50ms.")),
+                Err(_) => return Err(format!("Cannot parse number {ms} after {arg}.")),
+            },
+            None => return Err(format!("Missing number after {arg}.")),
+        },
+        _ => return Ok(false),
+    }
 
-    Some(color)
+    Ok(true)
 }
 
-// this returns the y offset for the fig font numbers to start printing from
-// a single line message will always be 1(since it prints on 0)
-// a fig font message will be > 1 unless something is borked with the font
-fn print_words(out: &mut Stdout, renderer: &Renderer, config: &AfkConfig) -> Result<u16, Box<dyn Error>> {
-    if config.words.is_empty() {
-        return Ok(1 + config.message_padding.1);
+// padding is either a single number used for both values or two numbers
+fn parse_padding<'a, I>(arg: &str, args: &mut Peekable<I>) -> Result<(u16, u16), String>
+where
+    I: Iterator<Item = &'a String>,
+{
+    let x = match args.next() {
+        Some(x) => x.parse().map_err(|_| format!("Cannot parse number {x} after {arg}."))?,
+        None => return Err(format!("Missing padding after {arg}.")),
+    };
+
+    match args.peek().map(|y| y.parse()) {
+        Some(Ok(y)) => {
+            args.next();
+            Ok((x, y))
+        }
+        _ => Ok((x, x)),
     }
+}
 
-    let words = if config.use_font {
-        let mut buf = Vec::with_capacity(config.words.len() * 8);
-        renderer.render(&config.words, &mut buf)?;
-        String::from_utf8(buf)?
-    } else {
-        config.words.clone()
+// a color followed by modifiers, e.g. red+bold+underline or "42 42 42+italic+on:blue"
+fn parse_style(spec: &str) -> Result<Style, String> {
+    let mut parts = spec.split('+').map(str::trim);
+
+    let mut style = match parts.next().unwrap_or_default() {
+        "" | "default" => Style::new(),
+        c => Style::new().fg(parse_color(c)?),
     };
 
-    let padding_x = " ".repeat(config.message_padding.0.into());
-    let words: String = iter::repeat("\r\n".to_owned())
-        .take(config.message_padding.1.into())
-        .chain(words.lines().filter(|l| !l.trim_end().is_empty()).map(|l| format!("{padding_x}{l}\r\n")))
-        .collect();
+    for modifier in parts {
+        style = match modifier.to_lowercase().as_ref() {
+            "bold" => style.bold(),
+            "italic" => style.italic(),
+            "underline" => style.underline(),
+            "dim" => style.dimmed(),
+            m => match m.strip_prefix("on:") {
+                Some(c) => style.on(parse_color(c)?),
+                None => {
+                    return Err(format!(
+                        "Unknown modifier {modifier}, expected bold, italic, underline, dim or on:color"
+                    ))
+                }
+            },
+        };
+    }
+
+    Ok(style)
+}
+
+// a duration and a style, e.g. 2m:yellow or 1:30:red+bold. the duration can contain colons too,
+// so it is everything up to the last colon that still leaves a duration in front of it
+fn parse_warning(warn: &str) -> Result<(i32, Style), String> {
+    let split = warn.match_indices(':').map(|(i, _)| i).rev().find(|i| duration::looks_like_duration(&warn[..*i]));
+
+    match split {
+        Some(i) => Ok((duration::parse_seconds(&warn[..i])?, parse_style(&warn[i + 1..])?)),
+        None => Err(format!("Cannot parse {warn}, expected a duration and a color like 2m:yellow")),
+    }
+}
 
-    out.queue(Print(config.style.paint(&words)))?;
+// the lines of the message, rendered with the message font when figlet mode is on
+fn render_words(config: &AfkConfig) -> Result<Vec<String>, Box<dyn Error>> {
+    if config.use_font {
+        return render_lines(&config.message_font, &config.words);
+    }
 
-    let offset = words.lines().count() as u16;
+    Ok(config.words.lines().filter(|l| !l.trim_end().is_empty()).map(ToString::to_string).collect())
+}
 
-    match offset {
-        1.. => Ok(offset),
-        0 => Ok(1),
+// figlet fonts pad their characters with blank lines, those are left out
+fn render_lines(renderer: &Renderer, text: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    if text.is_empty() {
+        return Ok(Vec::new());
     }
+
+    let mut buf = Vec::with_capacity(text.len() * 8);
+    renderer.render(text, &mut buf)?;
+    let text = String::from_utf8(buf)?;
+
+    Ok(text.lines().filter(|l| !l.trim_end().is_empty()).map(ToString::to_string).collect())
+}
+
+// puts `right` next to `left`, lined up at the bottom like the fraction after the seconds
+fn beside(left: Vec<String>, right: Vec<String>) -> Vec<String> {
+    let width = width_of(&left).into();
+    let height = left.len().max(right.len());
+    let pad = |lines: Vec<String>| iter::repeat_n(String::new(), height - lines.len()).chain(lines);
+
+    pad(left).zip(pad(right)).map(|(l, r)| format!("{l:width$}{r}")).collect()
+}
+
+fn width_of(lines: &[String]) -> u16 {
+    lines.iter().map(|l| l.chars().count()).max().unwrap_or(0) as u16
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
     let args = args().skip(1).collect::<Vec<_>>();
 
+    #[cfg(unix)]
+    if args.first().map(String::as_str) == Some("ctl") {
+        match control::send(&args[1..]) {
+            Ok(reply) => println!("{reply}"),
+            Err(e) => {
+                println!("{}", error_style().paint(e));
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+
     let mut config = if let Some(config) = parse_args(&args) {
         config
     } else {
@@ -279,95 +849,326 @@ fn main() -> Result<(), Box<dyn Error>> {
         return Ok(());
     };
 
-    let num_font = parse(include_str!("../resources/Ghost.flf").to_string()).expect("Failed to parse font: Ghost.flf");
-    let words_font = parse(include_str!("../resources/Big.flf").to_string()).expect("Failed to parse font: Big.flf");
+    let (tx, rx) = mpsc::channel();
+
+    #[cfg(unix)]
+    let _socket = match &config.control_socket {
+        Some(path) => match control::listen(path, tx.clone()) {
+            Ok(socket) => Some(socket),
+            Err(e) => {
+                let error = format!("Cannot listen on {}: {e}.", path.display());
+                println!("{}", error_style().paint(error));
+                return Ok(());
+            }
+        },
+        None => None,
+    };
 
     let mut stdout = init().expect("Failed to acquire stdout.");
 
-    let mut total_seconds = config.hours * 60 * 60 + config.minutes * 60 + config.seconds;
-    let mut old_lines: Vec<String> = Vec::new();
-    let mut offset_x = config.timer_padding.0;
+    let mut countdown = Countdown::new(config.total_seconds(), config.allow_negative);
+    let mut status: Option<(String, Instant)> = None;
+    let mut editing: Option<String> = None;
 
-    let (tx, rx) = mpsc::channel();
     events(tx.clone());
-    tick_timer(tx);
 
-    stdout.queue(MoveTo(0, 0))?;
+    let started = Instant::now();
+    let message_style = config.for_terminal(config.message_style);
+    let timer_style = config.for_terminal(config.timer_style);
+    let mut screen = Screen::new(config.for_terminal(Style::new()));
 
-    // print the message one time. resizing the window too small will erase whatever goes past the window edge
-    // cast now, so we don't cast muiltiple later
-    // SAFE/LOSSLESS: because it came from a u16 anyway
-    let offset_y = (print_words(&mut stdout, &Renderer::new(words_font), &config)? + config.timer_padding.1) as i32;
+    loop {
+        let total_seconds = countdown.remaining();
 
-    let renderer = Renderer::new(num_font);
+        // the message being edited stays put, or submitting it would replace whichever one came next
+        if editing.is_none() {
+            config.rotate_message();
+        }
 
-    loop {
-        if total_seconds == 0 && !config.allow_negative {
+        let formatted = config.format_time(total_seconds);
+        for hook in config.hooks.iter_mut() {
+            if hook.check(total_seconds) && config.clock.is_none() {
+                hook.run(total_seconds, formatted.clone(), &config.words, tx.clone());
+            }
+        }
+
+        if total_seconds == 0 && !config.allow_negative && config.clock.is_none() {
             config.flip_blinker();
         } else {
             config.is_blinking = false;
         }
 
-        let mut buf = Vec::new();
-        if !config.is_blinking {
-            let text = format_time(total_seconds, config.show_zeroes);
-            renderer.render(&text, &mut buf)?;
+        // with a fraction the seconds are no longer rounded up, 1.5 seconds left is 1.5 rather than 2.0
+        let ms = countdown.remaining_ms();
+        let mut text = config.format_time(total_seconds);
+        if config.precision > 0 {
+            text = config.format_time((ms / 1000) as i32);
+            // less than a second over has no whole seconds to carry the sign
+            if ms < 0 && config.format.is_none() && !text.starts_with('-') {
+                text.insert(0, '-');
+            }
         }
 
-        if let Ok(txt) = String::from_utf8(buf) {
-            let lines = txt.lines().map(ToString::to_string).collect::<Vec<_>>();
+        let now = Local::now();
+        if let Some(clock) = &config.clock {
+            text = clock.time(now);
+        }
 
-            for (i, line) in old_lines.drain(..).enumerate() {
-                let i = i as i32;
-                stdout.queue(MoveTo(offset_x, (offset_y + i) as u16))?;
-                stdout.queue(Print(" ".repeat(line.len())))?;
+        if let Some(output_file) = config.output_file.as_mut() {
+            if let Err(e) = output_file.update(&text, config.output_message.then_some(config.words.as_str())) {
+                status = Some((format!("Cannot write the output file: {e}"), Instant::now()));
             }
+        }
 
-            let mut num_y_offset = 0;
+        // the whole frame is drawn every time, the screen works out what actually changed
+        let message_lines = render_words(&config)?;
+        // the timer is rendered even while it blinks, so the layout does not jump around
+        let mut timer_lines = match (config.human && config.clock.is_none(), config.human_font) {
+            (true, true) => render_lines(&config.message_font, &human::phrase(total_seconds))?,
+            (true, false) => vec![human::phrase(total_seconds)],
+            _ => render_lines(&config.timer_font, &text)?,
+        };
+        if config.precision > 0 && !config.human && config.clock.is_none() {
+            let fraction = (ms.abs() % 1000) / 10_i64.pow(3 - config.precision);
+            let fraction = format!(".{fraction:0>width$}", width = config.precision as usize);
+            timer_lines = beside(timer_lines, render_lines(&config.fraction_font, &fraction)?);
+        }
 
-            if config.center_timer {
-                // if the text is empty it does not matter that we do not update the offset_x
-                if let Some(max_width) = lines.iter().map(String::len).max() {
-                    let term_width = terminal::size()?.0;
-                    offset_x = (term_width - max_width as u16) / 2;
-                }
-            }
+        screen.clear(terminal::size()?);
+        let size = screen.size();
+        let message = Block {
+            width: width_of(&message_lines),
+            // an empty message still takes up a line, like it always has
+            height: message_lines.len().max(1) as u16,
+            padding: config.message_padding,
+            align: config.message_align,
+            valign: config.message_valign,
+        };
+        // the progress bar or the date is part of the timer block, on the line below the digits
+        let timer_width = width_of(&timer_lines);
+        let bar = config.progress.as_ref().filter(|_| config.clock.is_none()).map(|progress| {
+            let total = config.total_seconds();
+            let done = if total > 0 { (total - total_seconds) as f32 / total as f32 } else { 1.0 };
+            progress.render(done, total_seconds < 0, progress.width.unwrap_or(timer_width.max(10)))
+        });
+        let bar_width = bar.as_ref().map_or(0, |(filled, track, label)| {
+            (filled.chars().count() + track.chars().count() + label.chars().count()) as u16
+        });
+        let date = config.clock.as_ref().and_then(|clock| clock.date(now));
+        let date_width = date.as_ref().map_or(0, |date| date.chars().count() as u16);
 
-            for (i, line) in lines.iter().enumerate() {
-                let i = i as i32;
-                if line.trim().is_empty() {
-                    num_y_offset += 1;
-                    continue;
-                }
-                stdout.queue(MoveTo(offset_x, (offset_y - num_y_offset + i) as u16))?;
-                stdout.queue(Print(config.style.paint(line)))?;
+        let timer = Block {
+            width: timer_width.max(bar_width).max(date_width),
+            height: timer_lines.len() as u16 + (bar.is_some() || date.is_some()) as u16,
+            padding: config.timer_padding,
+            align: config.timer_align,
+            valign: config.timer_valign,
+        };
+
+        // blocks sharing a vertical alignment are stacked in this order
+        let mut blocks = [message, timer];
+        if config.timer_first {
+            blocks.reverse();
+        }
+        let mut positions = layout::arrange(size, &blocks);
+        if config.timer_first {
+            positions.reverse();
+        }
+        let (message_pos, timer_pos) = (positions[0], positions[1]);
+
+        screen.draw(message_pos, &message_lines, config.paint(message_style, started.elapsed()));
+
+        // the clock has no time running out
+        let warning_style = config.warning_style(total_seconds).filter(|_| config.clock.is_none());
+
+        if !config.is_blinking {
+            // warnings are meant to stand out, so they replace any gradient
+            let paint = match warning_style {
+                Some(style) => Paint::Solid(config.for_terminal(style)),
+                None => config.paint(timer_style, started.elapsed()),
+            };
+            let x = timer_pos.0 + config.timer_align.offset(timer_width, timer.width);
+            screen.draw((x, timer_pos.1), &timer_lines, paint);
+        }
+
+        if let (Some(progress), Some((filled, track, label))) = (&config.progress, bar) {
+            let style = progress.style.or(warning_style).unwrap_or(config.timer_style);
+            let track_style = progress.track_style.unwrap_or_else(|| config.timer_style.dimmed());
+            let label_style = if total_seconds < 0 { config.overtime.unwrap_or(style) } else { style };
+
+            let mut x = timer_pos.0 + config.timer_align.offset(bar_width, timer.width);
+            let y = timer_pos.1 + timer_lines.len() as u16;
+            for (part, style) in [(filled, style), (track, track_style), (label, label_style)] {
+                let len = part.chars().count() as u16;
+                screen.draw((x, y), &[part], Paint::Solid(config.for_terminal(style)));
+                x += len;
             }
+        }
+
+        if let Some(date) = date {
+            let x = timer_pos.0 + config.timer_align.offset(date_width, timer.width);
+            screen.draw((x, timer_pos.1 + timer_lines.len() as u16), &[date], Paint::Solid(timer_style));
+        }
+
+        // leave a blank line between the timer and the indicator, or go above the timer when there is no room below
+        if countdown.is_paused() {
+            let (x, y) = timer_pos;
+            let below = y + timer.height + 1;
+            let y = if below < size.1 { below } else { y.saturating_sub(2) };
+            screen.draw((x, y), &[PAUSED.to_string()], Paint::Solid(timer_style));
+        }
+
+        status = status.filter(|(_, shown)| shown.elapsed() < STATUS_DURATION);
+        if let Some((error, _)) = &status {
+            let red = config.for_terminal(Style::new().fg(Colour::Red));
+            screen.draw((0, size.1.saturating_sub(1)), slice::from_ref(error), Paint::Solid(red));
+        }
+
+        // the prompt goes just above the status line, keeping the end of long messages in view
+        if let Some(words) = &editing {
+            let prompt = format!("{EDIT_PROMPT}{words}_");
+            let skip = prompt.chars().count().saturating_sub(size.0.into());
+            let prompt = prompt.chars().skip(skip).collect::<String>();
+            screen.draw((0, size.1.saturating_sub(2)), &[prompt], Paint::Solid(message_style));
+        }
 
-            old_lines = lines;
-            stdout.flush()?;
+        screen.flush(&mut stdout)?;
+
+        // sleep until something changes: the timer ticking over, a key, a command, or anything else that
+        // is shown for a while
+        let mut wait = match (&config.clock, config.precision) {
+            (Some(clock), _) => Some(clock.until_next(Local::now())),
+            (None, 0) => countdown.until_next(1000),
+            (None, digits) => countdown.until_next(10_i64.pow(3 - digits)).map(|wait| wait.max(FRACTION_FRAME)),
+        }
+        .unwrap_or(Duration::MAX);
+        if config.blink_rate > 0
+            && (config.is_blinking || (total_seconds == 0 && !config.allow_negative && config.clock.is_none()))
+        {
+            wait = wait.min(Duration::from_millis(config.blink_rate).saturating_sub(config.blink_timer.elapsed()));
+        }
+        if config.messages.len() > 1 && editing.is_none() {
+            wait = wait.min(Duration::from_secs(config.rotate_every).saturating_sub(config.rotated_at.elapsed()));
         }
+        if let Some((_, shown)) = &status {
+            wait = wait.min(STATUS_DURATION.saturating_sub(shown.elapsed()));
+        }
+        if config.gradient.is_some() && config.animate {
+            wait = wait.min(ANIMATION_FRAME);
+        }
+
+        let first = match rx.recv_timeout(wait) {
+            Ok(app_event) => Some(app_event),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
 
-        if let Ok(app_event) = rx.try_recv() {
+        // everything that came in is handled before the next frame
+        let mut quit = false;
+        for app_event in first.into_iter().chain(rx.try_iter()) {
             match app_event {
-                AppEvent::Tick => {
-                    if total_seconds > 0 || config.allow_negative {
-                        total_seconds -= 1;
+                AppEvent::Quit => quit = true,
+                // the clock has no timer to change
+                AppEvent::ModifyTimer(_) | AppEvent::Pause | AppEvent::Reset | AppEvent::Restart
+                    if config.clock.is_some() => {}
+                #[cfg(unix)]
+                AppEvent::SetTimer(_) | AppEvent::SetPaused(_) if config.clock.is_some() => {}
+                AppEvent::ModifyTimer(s) => countdown.add(s),
+                AppEvent::Pause => countdown.set_paused(!countdown.is_paused()),
+                // reset waits for the timer to be resumed, restart starts counting down again right away
+                AppEvent::Reset | AppEvent::Restart => {
+                    countdown = Countdown::new(config.total_seconds(), config.allow_negative);
+                    countdown.set_paused(matches!(app_event, AppEvent::Reset));
+                    config.reset_blinker();
+                }
+                AppEvent::CommandFailed(error) => status = Some((error, Instant::now())),
+                #[cfg(unix)]
+                AppEvent::SetTimer(s) => {
+                    countdown.set(s as i64 * 1000);
+                    config.reset_blinker();
+                }
+                #[cfg(unix)]
+                AppEvent::SetPaused(p) => countdown.set_paused(p),
+                #[cfg(unix)]
+                AppEvent::SetMessage(words) => config.set_message(words),
+                AppEvent::Edit(Edit::Start) => editing = Some(config.words.clone()),
+                AppEvent::Edit(Edit::Char(c)) => {
+                    if let Some(words) = editing.as_mut() {
+                        words.push(c);
+                    }
+                }
+                AppEvent::Edit(Edit::Backspace) => {
+                    if let Some(words) = editing.as_mut() {
+                        words.pop();
                     }
                 }
-                AppEvent::Quit => break,
-                AppEvent::ModifyTimer(s) => {
-                    total_seconds += s;
-                    if total_seconds < 0 && !config.allow_negative {
-                        total_seconds = 0;
+                AppEvent::Edit(Edit::Submit) => {
+                    if let Some(words) = editing.take() {
+                        config.set_message(words);
                     }
                 }
+                AppEvent::Edit(Edit::Cancel) => editing = None,
+                // the terminal may have mangled what was on the screen
+                AppEvent::Resize => screen.invalidate(),
+                #[cfg(unix)]
+                AppEvent::Status(reply) => {
+                    let remaining = countdown.remaining();
+                    let _ = reply.send(format!(
+                        "remaining={remaining} time={} paused={} message={:?}",
+                        config.format_time(remaining),
+                        countdown.is_paused(),
+                        config.words
+                    ));
+                }
             }
         }
 
-        thread::sleep(Duration::from_millis(100));
+        if quit {
+            break;
+        }
     }
 
     cleanup(&mut stdout);
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_warnings() {
+        assert_eq!(parse_warning("2m:yellow"), Ok((120, Style::new().fg(Colour::Yellow))));
+        assert_eq!(parse_warning("30:red+bold"), Ok((30, Style::new().fg(Colour::Red).bold())));
+        // the colon inside the duration is not the one before the color
+        assert_eq!(parse_warning("1:30:#ff0000"), Ok((90, Style::new().fg(Colour::RGB(255, 0, 0)))));
+        assert!(parse_warning("yellow").is_err());
+        assert!(parse_warning("2m:nope").is_err());
+    }
+
+    #[test]
+    fn picks_the_closest_warning() {
+        let mut config = AfkConfig { minutes: 10, ..AfkConfig::default() };
+        config.warnings = vec![(120, Style::new().fg(Colour::Yellow)), (30, Style::new().fg(Colour::Red))];
+
+        assert_eq!(config.warning_style(300), None);
+        assert_eq!(config.warning_style(120), None);
+        assert_eq!(config.warning_style(119), Some(Style::new().fg(Colour::Yellow)));
+        assert_eq!(config.warning_style(10), Some(Style::new().fg(Colour::Red)));
+        assert_eq!(config.warning_style(-5), Some(Style::new().fg(Colour::Red)));
+
+        config.overtime = Some(Style::new().fg(Colour::Purple));
+        assert_eq!(config.warning_style(-5), Some(Style::new().fg(Colour::Purple)));
+        assert_eq!(config.warning_style(0), Some(Style::new().fg(Colour::Red)));
+    }
+
+    #[test]
+    fn fades_as_the_time_runs_out() {
+        let config = AfkConfig { minutes: 10, fade: Some(Gradient::fade()), ..AfkConfig::default() };
+
+        assert_eq!(config.warning_style(600), Some(Style::new().fg(Colour::RGB(0, 205, 0))));
+        assert_eq!(config.warning_style(300), Some(Style::new().fg(Colour::RGB(205, 205, 0))));
+        assert_eq!(config.warning_style(0), Some(Style::new().fg(Colour::RGB(205, 0, 0))));
+    }
+}
@@ -0,0 +1,43 @@
+// Mirrors the timer into a plain text file, e.g. for OBS text sources that read from a file.
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+pub struct OutputFile {
+    path: PathBuf,
+    last: String,
+}
+
+impl OutputFile {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path, last: String::new() }
+    }
+
+    // only touches the file when the text changed
+    pub fn update(&mut self, time: &str, message: Option<&str>) -> io::Result<()> {
+        let text = match message.filter(|m| !m.is_empty()) {
+            Some(message) => format!("{message}\n{time}\n"),
+            None => format!("{time}\n"),
+        };
+
+        if text == self.last {
+            return Ok(());
+        }
+
+        write_atomic(&self.path, &text)?;
+        self.last = text;
+        Ok(())
+    }
+}
+
+// readers never see a half written file, as the rename replaces it in one go
+fn write_atomic(path: &Path, text: &str) -> io::Result<()> {
+    let file_name = path.file_name().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "not a file"))?;
+    let mut tmp_name = file_name.to_os_string();
+    tmp_name.push(".tmp");
+    let tmp = path.with_file_name(tmp_name);
+
+    fs::write(&tmp, text)?;
+    fs::rename(&tmp, path)
+}
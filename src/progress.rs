@@ -0,0 +1,104 @@
+// A bar under the timer that fills up as the time runs out.
+use ansi_term::Style;
+
+#[derive(Clone, Copy, Default)]
+pub enum Fill {
+    #[default]
+    Block,
+    Half,
+    Braille,
+}
+
+impl Fill {
+    pub fn parse(fill: &str) -> Option<Self> {
+        match fill.to_lowercase().as_ref() {
+            "block" => Some(Self::Block),
+            "half" => Some(Self::Half),
+            "braille" => Some(Self::Braille),
+            _ => None,
+        }
+    }
+
+    // a full cell, the partly filled cells from least to most, and an empty cell
+    fn chars(self) -> (char, &'static [char], char) {
+        match self {
+            Self::Block => ('█', &[], '░'),
+            Self::Half => ('█', &['▌'], '░'),
+            Self::Braille => ('⣿', &['⡀', '⡄', '⡆', '⡇', '⣇', '⣧', '⣷'], '⣀'),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Progress {
+    // defaults to the width of the timer
+    pub width: Option<u16>,
+    pub fill: Fill,
+    // default to the timer style, and a dimmed timer style for the empty part
+    pub style: Option<Style>,
+    pub track_style: Option<Style>,
+    pub percent: bool,
+}
+
+impl Progress {
+    // the filled part, the empty part and the label after the bar. `done` goes from 0 to 1,
+    // and the bar stays full in overtime
+    pub fn render(&self, done: f32, overtime: bool, width: u16) -> (String, String, String) {
+        let done = if overtime { 1.0 } else { done.clamp(0.0, 1.0) };
+        let (full, partial, empty) = self.fill.chars();
+        let width = width as usize;
+
+        let steps = partial.len() + 1;
+        let filled = (done * (width * steps) as f32).floor() as usize;
+        let mut bar = full.to_string().repeat(filled / steps);
+        if !filled.is_multiple_of(steps) {
+            bar.push(partial[filled % steps - 1]);
+        }
+        let track = empty.to_string().repeat(width - bar.chars().count());
+
+        let label = if overtime {
+            " OVERTIME".to_string()
+        } else if self.percent {
+            format!(" {:>3}%", (done * 100.0).floor())
+        } else {
+            String::new()
+        };
+
+        (bar, track, label)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(fill: Fill, done: f32, width: u16) -> (String, String) {
+        let (bar, track, _) = Progress { fill, ..Progress::default() }.render(done, false, width);
+        (bar, track)
+    }
+
+    #[test]
+    fn fills_whole_cells() {
+        assert_eq!(render(Fill::Block, 0.0, 4), ("".into(), "░░░░".into()));
+        assert_eq!(render(Fill::Block, 0.5, 4), ("██".into(), "░░".into()));
+        assert_eq!(render(Fill::Block, 0.6, 4), ("██".into(), "░░".into()));
+        assert_eq!(render(Fill::Block, 2.0, 4), ("████".into(), "".into()));
+    }
+
+    #[test]
+    fn fills_partial_cells() {
+        assert_eq!(render(Fill::Half, 0.625, 4), ("██▌".into(), "░".into()));
+        assert_eq!(render(Fill::Braille, 9.0 / 32.0, 4), ("⣿⡀".into(), "⣀⣀".into()));
+        assert_eq!(render(Fill::Braille, 15.0 / 32.0, 4), ("⣿⣷".into(), "⣀⣀".into()));
+    }
+
+    #[test]
+    fn labels() {
+        let progress = Progress { percent: true, ..Progress::default() };
+        assert_eq!(progress.render(0.426, false, 4).2, "  42%");
+        assert_eq!(progress.render(1.0, false, 4).2, " 100%");
+
+        let (bar, track, label) = progress.render(0.2, true, 4);
+        assert_eq!((bar.as_str(), track.as_str(), label.as_str()), ("████", "", " OVERTIME"));
+    }
+}
@@ -0,0 +1,115 @@
+// A double buffered screen. Every frame is drawn in full into the back buffer, and only the cells that
+// changed since the last frame are sent to the terminal.
+use std::{
+    error::Error,
+    io::{Stdout, Write},
+};
+
+use ansi_term::Style;
+use crossterm::{
+    cursor::MoveTo,
+    style::Print,
+    terminal::{Clear, ClearType},
+    QueueableCommand,
+};
+
+use crate::gradient::Paint;
+
+#[derive(Clone, Copy, PartialEq)]
+struct Cell {
+    ch: char,
+    style: Style,
+}
+
+pub struct Screen {
+    size: (u16, u16),
+    blank: Style,
+    cells: Vec<Cell>,
+    // what the terminal shows right now, or nothing when it has to be drawn from scratch
+    shown: Option<Vec<Cell>>,
+}
+
+impl Screen {
+    pub fn new(blank: Style) -> Self {
+        Self { size: (0, 0), blank, cells: Vec::new(), shown: None }
+    }
+
+    pub fn size(&self) -> (u16, u16) {
+        self.size
+    }
+
+    // starts a new frame with nothing on it
+    pub fn clear(&mut self, size: (u16, u16)) {
+        if size != self.size {
+            self.size = size;
+            self.shown = None;
+        }
+
+        let blank = Cell { ch: ' ', style: self.blank };
+        self.cells.clear();
+        self.cells.resize(size.0 as usize * size.1 as usize, blank);
+    }
+
+    // draws everything again on the next flush, e.g. when the terminal may have mangled what was shown
+    pub fn invalidate(&mut self) {
+        self.shown = None;
+    }
+
+    // puts the lines at the top left corner, cut off at the edge of the screen rather than wrapping
+    pub fn draw(&mut self, (x, y): (u16, u16), lines: &[String], paint: Paint) {
+        let (width, height) = (self.size.0 as usize, self.size.1 as usize);
+        let block_size = (lines.iter().map(|l| l.chars().count()).max().unwrap_or(0), lines.len());
+
+        for (row, line) in lines.iter().enumerate() {
+            let y = y as usize + row;
+            if y >= height {
+                break;
+            }
+
+            for (col, ch) in line.chars().enumerate().take(width.saturating_sub(x as usize)) {
+                let style = paint.style_at(ch, col, row, block_size);
+                self.cells[y * width + x as usize + col] = Cell { ch, style };
+            }
+        }
+    }
+
+    pub fn flush(&mut self, out: &mut Stdout) -> Result<(), Box<dyn Error>> {
+        let shown = match self.shown.take() {
+            Some(shown) => shown,
+            None => {
+                // clearing while the background color is set fills the whole terminal with it
+                out.queue(Print(self.blank.prefix()))?;
+                out.queue(Clear(ClearType::All))?;
+                out.queue(Print(self.blank.suffix()))?;
+                vec![Cell { ch: ' ', style: self.blank }; self.cells.len()]
+            }
+        };
+
+        let width = (self.size.0 as usize).max(1);
+        for (y, (row, shown_row)) in self.cells.chunks(width).zip(shown.chunks(width)).enumerate() {
+            let mut x = 0;
+            while x < row.len() {
+                if row[x] == shown_row[x] {
+                    x += 1;
+                    continue;
+                }
+
+                // a run of changed cells is printed in one go, with the style only changing where it has to
+                let start = x;
+                while x < row.len() && row[x] != shown_row[x] {
+                    x += 1;
+                }
+
+                out.queue(MoveTo(start as u16, y as u16))?;
+                for cells in row[start..x].chunk_by(|a, b| a.style == b.style) {
+                    let text = cells.iter().map(|c| c.ch).collect::<String>();
+                    out.queue(Print(cells[0].style.paint(text)))?;
+                }
+            }
+        }
+
+        self.shown = Some(self.cells.clone());
+        out.flush()?;
+        Ok(())
+    }
+}